@@ -1,4 +1,7 @@
 use windows_notifier::{
+    arguments::ToastArguments,
+    notifier::Notifier,
+    queue::{Backpressure, DeliveryQueue},
     tags::{audio::Audio, image::Image, text::Text},
     Toast,
 };
@@ -10,7 +13,7 @@ async fn main() {
         .add_audio(Audio::new(
             windows_notifier::tags::audio::Notification::Default,
         ))
-        .unwrap(); /// i want this to panic \ not compile;
+        .unwrap(); // audio can only be added once, the rest of the builder is still available
     toast.title("Hello, world!").unwrap();
 
     toast.add_text(Text::new("Jill Bender")).unwrap();
@@ -42,13 +45,28 @@ async fn main() {
         .add_action(
             windows_notifier::tags::action::Action::new(
                 "Send".into(),
-                "action=reply&amp;threadId=92187".into(),
+                ToastArguments::new()
+                    .add("action", "reply")
+                    .add("threadId", 92187),
             )
             .image_uri("".to_string())
             .hint_input_id("textbox".to_string())
-            .activation_type(windows_notifier::tags::action::ActivationType::Background),
+            .activation_type(windows_notifier::tags::activation::ActivationType::Background),
         )
         .unwrap();
 
-    toast.show().unwrap();
+    let notifier = Notifier::new(Notifier::POWERSHELL_APP_ID);
+    let queue = DeliveryQueue::new(&notifier, 16)
+        .unwrap()
+        .backpressure(Backpressure::DropOldest);
+    let outcome = queue
+        .show(&toast)
+        .await
+        .unwrap()
+        .into_events()
+        .wait_async(std::time::Duration::from_secs(30))
+        .await
+        .unwrap();
+    println!("{:?}", outcome);
+    queue.shutdown().await;
 }
@@ -0,0 +1,211 @@
+//! Arguments for the toast, its actions, header and commands, as ordered key/value pairs.
+//!
+//! They are written like the Windows Community Toolkit writes them, `action=reply;threadId=92187`,
+//! so apps using the toolkit can read them, and read back from the arguments of an
+//! [activation](crate::events::ToastEvent::Activated).
+//!
+//! ```
+//! # use windows_notifier::{arguments::ToastArguments, tags::action::Action};
+//! let arguments = ToastArguments::new()
+//!     .add("action", "reply")
+//!     .add("threadId", 92187);
+//! let action = Action::new("Reply".into(), arguments);
+//! assert_eq!(action.get_arguments(), "action=reply;threadId=92187");
+//!
+//! let arguments: ToastArguments = action.get_arguments().parse()?;
+//! assert_eq!(arguments.get("action"), Some("reply"));
+//! assert_eq!(arguments.get_parsed::<u64>("threadId")?, 92187);
+//! # Ok::<(), windows_notifier::error::Error>(())
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+/// Ordered key/value pairs, where a key can also stand on its own without a value.
+///
+/// `%`, `;` and `=` in keys and values are escaped as `%25`, `%3B` and `%3D`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ToastArguments {
+    pairs: Vec<(String, Option<String>)>,
+}
+
+impl ToastArguments {
+    pub fn new() -> ToastArguments {
+        ToastArguments::default()
+    }
+
+    /// Set `key` to `value`, keeping its position if it is already there.
+    pub fn add(mut self, key: &str, value: impl fmt::Display) -> Self {
+        self.set(key, Some(value.to_string()));
+        self
+    }
+
+    /// Add `key` without a value, like `markAsRead`.
+    pub fn add_key(mut self, key: &str) -> Self {
+        self.set(key, None);
+        self
+    }
+
+    fn set(&mut self, key: &str, value: Option<String>) {
+        match self.pairs.iter_mut().find(|(k, _)| k == key) {
+            Some((_, current)) => *current = value,
+            None => self.pairs.push((key.into(), value)),
+        }
+    }
+
+    /// Remove `key`, returning whether it was there.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.pairs.len();
+        self.pairs.retain(|(k, _)| k != key);
+        self.pairs.len() != len
+    }
+
+    /// Whether `key` is there, with or without a value.
+    pub fn contains(&self, key: &str) -> bool {
+        self.pairs.iter().any(|(k, _)| k == key)
+    }
+
+    /// The value of `key`, `None` if it is missing or has no value.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// The value of `key` parsed as `T`.
+    ///
+    /// Returns [`Error::ActivationParse`] if it is missing or does not parse.
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Result<T>
+    where
+        T::Err: fmt::Display,
+    {
+        let value = self.get(key).ok_or_else(|| Error::ActivationParse {
+            input: self.to_string(),
+            message: format!("{} has no value", key),
+        })?;
+        value.parse().map_err(|err| Error::ActivationParse {
+            input: value.into(),
+            message: format!("{}: {}", key, err),
+        })
+    }
+
+    /// The pairs in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.pairs
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_deref()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+impl fmt::Display for ToastArguments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (key, value)) in self.pairs.iter().enumerate() {
+            if index > 0 {
+                f.write_str(";")?;
+            }
+            f.write_str(&escape(key))?;
+            if let Some(value) = value {
+                write!(f, "={}", escape(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ToastArguments {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<ToastArguments> {
+        let mut arguments = ToastArguments::new();
+        for pair in input.split(';').filter(|pair| !pair.is_empty()) {
+            let (key, value) = match pair.split_once('=') {
+                Some((key, value)) => (key, Some(unescape(input, value)?)),
+                None => (pair, None),
+            };
+            arguments.set(&unescape(input, key)?, value);
+        }
+        Ok(arguments)
+    }
+}
+
+impl From<ToastArguments> for String {
+    fn from(arguments: ToastArguments) -> String {
+        arguments.to_string()
+    }
+}
+
+impl<K: AsRef<str>, V: fmt::Display> FromIterator<(K, V)> for ToastArguments {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(ToastArguments::new(), |arguments, (key, value)| {
+                arguments.add(key.as_ref(), value)
+            })
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace(';', "%3B")
+        .replace('=', "%3D")
+}
+
+fn unescape(input: &str, value: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(index) = rest.find('%') {
+        unescaped.push_str(&rest[..index]);
+        let escaped = rest.get(index..index + 3).unwrap_or(&rest[index..]);
+        unescaped.push(match escaped.to_ascii_uppercase().as_str() {
+            "%25" => '%',
+            "%3B" => ';',
+            "%3D" => '=',
+            _ => {
+                return Err(Error::ActivationParse {
+                    input: input.into(),
+                    message: format!("unknown escape {:?}", escaped),
+                })
+            }
+        });
+        rest = &rest[index + escaped.len()..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arguments::ToastArguments;
+
+    #[test]
+    fn escapes_round_trip() {
+        let arguments = ToastArguments::new()
+            .add("action", "reply")
+            .add("text", "50% off; a=b")
+            .add_key("markAsRead")
+            .add("action", "open");
+        let written = arguments.to_string();
+        assert_eq!(written, "action=open;text=50%25 off%3B a%3Db;markAsRead");
+
+        let parsed: ToastArguments = written.parse().unwrap();
+        assert_eq!(parsed, arguments);
+        assert_eq!(parsed.get("text"), Some("50% off; a=b"));
+        assert!(parsed.contains("markAsRead"));
+        assert_eq!(parsed.get("markAsRead"), None);
+        assert!(parsed.get_parsed::<u32>("action").is_err());
+
+        assert!("a=100%".parse::<ToastArguments>().is_err());
+        assert!("".parse::<ToastArguments>().unwrap().is_empty());
+    }
+}
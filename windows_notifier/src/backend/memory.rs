@@ -0,0 +1,501 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::error::{Error, Result};
+use crate::events::{DismissReason, EventSender, ToastEvent};
+use crate::history::MAX_HISTORY;
+use crate::identity::ToastIdentity;
+use crate::schedule::{Clock, SystemClock};
+use crate::tags::activation::AfterActivationBehavior;
+use crate::Toast;
+
+use super::{Backend, NotificationData, ShownToast, ToastPayload, UpdateResult};
+
+/// A toast shown by the [`MemoryBackend`].
+#[derive(Debug, Clone)]
+pub struct MemoryToast {
+    pub app_id: String,
+    pub payload: ToastPayload,
+    /// Whether the toast is still on screen, false once it timed out into the Action Center.
+    pub on_screen: bool,
+    events: EventSender,
+    shown: u64,
+}
+
+/// What the [`MemoryBackend`] keeps in a [`ShownToast`], the number of the show.
+struct MemoryShown(u64);
+
+/// Keeps shown toasts in memory instead of showing them, for tests and platforms without toasts.
+///
+/// Toasts behave like on windows: a toast replaces the shown toast of the same app with the same tag and group,
+/// updates follow the sequence number rules of [`NotificationData::sequence_number`],
+/// each app keeps at most [`MAX_HISTORY`] toasts, dropping the oldest,
+/// and expired toasts are gone the next time the backend is used.
+/// Replaced, expired, dropped and removed toasts get a dismissal with [`DismissReason::Replaced`],
+/// [`DismissReason::Expired`], [`DismissReason::HistoryFull`] or [`DismissReason::ApplicationHidden`].
+pub struct MemoryBackend {
+    toasts: Mutex<Vec<MemoryToast>>,
+    shows: AtomicU64,
+    history_cap: usize,
+    clock: Box<dyn Clock>,
+}
+
+impl fmt::Debug for MemoryBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryBackend")
+            .field("toasts", &self.toasts)
+            .field("history_cap", &self.history_cap)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        MemoryBackend::new()
+    }
+}
+
+impl MemoryBackend {
+    pub fn new() -> MemoryBackend {
+        MemoryBackend {
+            toasts: Mutex::new(Vec::new()),
+            shows: AtomicU64::new(0),
+            history_cap: MAX_HISTORY,
+            clock: Box::new(SystemClock),
+        }
+    }
+
+    /// Expire toasts by `clock` instead of the [`SystemClock`].
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Drop the toasts that [expire on reboot](crate::Toast::expires_on_reboot), as if the system restarted.
+    pub fn reboot(&self) {
+        self.lock().retain(|toast| !toast.payload.expires_on_reboot);
+    }
+
+    /// Keep at most `history_cap` toasts per app instead of [`MAX_HISTORY`].
+    pub fn history_cap(mut self, history_cap: usize) -> Self {
+        self.history_cap = history_cap;
+        self
+    }
+
+    /// The shown toasts, oldest first.
+    pub fn toasts(&self) -> Vec<MemoryToast> {
+        self.lock().clone()
+    }
+
+    /// Deliver an activation of the toast at `index` in [`MemoryBackend::toasts`], which removes it like on windows,
+    /// unless it activates a button with [`AfterActivationBehavior::PendingUpdate`].
+    /// Returns false if there is no such toast.
+    pub fn activate(
+        &self,
+        index: usize,
+        arguments: &str,
+        user_inputs: HashMap<String, String>,
+    ) -> bool {
+        let pending = self.lock().get(index).is_some_and(|toast| {
+            Toast::from_xml(&toast.payload.xml).is_ok_and(|toast| {
+                toast.actions().iter().any(|action| {
+                    action.get_arguments() == arguments
+                        && action.get_after_activation_behavior()
+                            == Some(AfterActivationBehavior::PendingUpdate)
+                })
+            })
+        });
+        self.deliver(
+            index,
+            !pending,
+            ToastEvent::Activated {
+                arguments: arguments.into(),
+                user_inputs,
+            },
+        )
+    }
+
+    /// Deliver a dismissal of the toast at `index`.
+    /// A toast that timed out stays in the history, off screen, the others are removed.
+    pub fn dismiss(&self, index: usize, reason: DismissReason) -> bool {
+        let timed_out = reason == DismissReason::TimedOut;
+        if timed_out {
+            if let Some(toast) = self.lock().get_mut(index) {
+                toast.on_screen = false;
+            }
+        }
+        self.deliver(index, !timed_out, ToastEvent::Dismissed { reason })
+    }
+
+    /// Deliver that the toast at `index` could not be shown, which removes it.
+    pub fn fail(&self, index: usize, error: Error) -> bool {
+        self.deliver(index, true, ToastEvent::Failed { error })
+    }
+
+    fn deliver(&self, index: usize, remove: bool, event: ToastEvent) -> bool {
+        let mut toasts = self.lock();
+        let Some(toast) = toasts.get(index) else {
+            return false;
+        };
+        toast.events.send(event);
+        if remove {
+            toasts.remove(index);
+        }
+        true
+    }
+
+    /// Remove the toasts matching `remove`, sending each a dismissal with `reason`.
+    fn dismiss_where(
+        toasts: &mut Vec<MemoryToast>,
+        reason: DismissReason,
+        mut remove: impl FnMut(&MemoryToast) -> bool,
+    ) {
+        toasts.retain(|toast| {
+            let removed = remove(toast);
+            if removed {
+                toast.events.send(ToastEvent::Dismissed { reason });
+            }
+            !removed
+        });
+    }
+
+    fn find<'t>(
+        toasts: &'t mut [MemoryToast],
+        app_id: &str,
+        identity: &ToastIdentity,
+    ) -> Option<&'t mut MemoryToast> {
+        toasts
+            .iter_mut()
+            .find(|toast| toast.app_id == app_id && toast.payload.identity.replaces(identity))
+    }
+
+    /// The toasts, without the ones that expired.
+    fn lock(&self) -> MutexGuard<'_, Vec<MemoryToast>> {
+        // A panic while holding the lock can not leave the list half changed.
+        let mut toasts = self.toasts.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = self.clock.now();
+        MemoryBackend::dismiss_where(&mut toasts, DismissReason::Expired, |toast| {
+            toast
+                .payload
+                .expiration
+                .is_some_and(|expiration| expiration <= now)
+        });
+        toasts
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn show(
+        &self,
+        app_id: &str,
+        payload: &ToastPayload,
+        events: EventSender,
+    ) -> Result<ShownToast> {
+        let number = self.shows.fetch_add(1, Ordering::Relaxed);
+        let mut toasts = self.lock();
+        MemoryBackend::dismiss_where(&mut toasts, DismissReason::Replaced, |toast| {
+            toast.app_id == app_id && toast.payload.identity.replaces(&payload.identity)
+        });
+        toasts.push(MemoryToast {
+            app_id: app_id.into(),
+            payload: payload.clone(),
+            on_screen: true,
+            events,
+            shown: number,
+        });
+        let shown = toasts.iter().filter(|toast| toast.app_id == app_id).count();
+        if shown > self.history_cap {
+            let mut over = shown - self.history_cap;
+            MemoryBackend::dismiss_where(&mut toasts, DismissReason::HistoryFull, |toast| {
+                let drop = over > 0 && toast.app_id == app_id;
+                over -= drop as usize;
+                drop
+            });
+        }
+        Ok(ShownToast::new(MemoryShown(number)))
+    }
+
+    fn update(
+        &self,
+        app_id: &str,
+        identity: &ToastIdentity,
+        data: &NotificationData,
+    ) -> Result<UpdateResult> {
+        Ok(
+            match MemoryBackend::find(&mut self.lock(), app_id, identity) {
+                Some(toast) => toast
+                    .payload
+                    .data
+                    .get_or_insert_with(NotificationData::new)
+                    .apply(data),
+                None => UpdateResult::NotificationNotFound,
+            },
+        )
+    }
+
+    fn hide(&self, app_id: &str, identity: &ToastIdentity, shown: &ShownToast) -> Result<()> {
+        let shown = shown.get::<MemoryShown>().map(|shown| shown.0);
+        MemoryBackend::dismiss_where(
+            &mut self.lock(),
+            DismissReason::ApplicationHidden,
+            |toast| {
+                let found = match shown {
+                    Some(shown) => toast.shown == shown,
+                    None => toast.payload.identity.replaces(identity),
+                };
+                toast.on_screen && toast.app_id == app_id && found
+            },
+        );
+        Ok(())
+    }
+
+    fn remove(&self, app_id: &str, identity: &ToastIdentity) -> Result<()> {
+        MemoryBackend::dismiss_where(
+            &mut self.lock(),
+            DismissReason::ApplicationHidden,
+            |toast| toast.app_id == app_id && toast.payload.identity.replaces(identity),
+        );
+        Ok(())
+    }
+
+    fn history(&self, app_id: &str) -> Result<Vec<ToastPayload>> {
+        Ok(self
+            .lock()
+            .iter()
+            .filter(|toast| toast.app_id == app_id)
+            .map(|toast| toast.payload.clone())
+            .collect())
+    }
+
+    fn remove_group(&self, app_id: &str, group: &str) -> Result<()> {
+        MemoryBackend::dismiss_where(
+            &mut self.lock(),
+            DismissReason::ApplicationHidden,
+            |toast| toast.app_id == app_id && toast.payload.identity.get_group() == Some(group),
+        );
+        Ok(())
+    }
+
+    fn clear(&self, app_id: &str) -> Result<()> {
+        MemoryBackend::dismiss_where(
+            &mut self.lock(),
+            DismissReason::ApplicationHidden,
+            |toast| toast.app_id == app_id,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::{MemoryBackend, NotificationData, UpdateResult};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::error::Error;
+    use crate::events::{DismissReason, ToastEvent};
+    use crate::identity::ToastIdentity;
+    use crate::notifier::Notifier;
+    use crate::schedule::ManualClock;
+    use crate::tags::progress::Progress;
+    use crate::Toast;
+
+    #[test]
+    fn replaces_same_identity() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+        let show = |title: &str, identity: ToastIdentity| {
+            let mut toast = Toast::new().unwrap();
+            toast.title(title).unwrap();
+            toast.identity(identity).unwrap();
+            notifier.show(&toast).unwrap()
+        };
+        let thread = ToastIdentity::new("thread-1")
+            .unwrap()
+            .group("chat")
+            .unwrap();
+        show("Hi", thread.clone());
+        show("Untagged", ToastIdentity::default());
+        show("Other group", ToastIdentity::new("thread-1").unwrap());
+        let handle = show("Are you there?", thread.clone().remote_id("m-2"));
+
+        let titles: Vec<_> = backend
+            .toasts()
+            .iter()
+            .map(|toast| toast.payload.xml.contains("Are you there?"))
+            .collect();
+        assert_eq!(titles, [false, false, true]);
+        assert_eq!(handle.get_identity().get_remote_id(), Some("m-2"));
+
+        handle.remove().unwrap();
+        handle.remove().unwrap();
+        assert_eq!(backend.toasts().len(), 2);
+        assert!(show("Untagged", ToastIdentity::default()).remove().is_err());
+    }
+
+    #[test]
+    fn delivers_events() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+        let mut toast = Toast::new().unwrap();
+        toast.tag("build").unwrap();
+        let first = notifier.show(&toast).unwrap();
+        assert!(backend.dismiss(0, DismissReason::TimedOut));
+        assert!(matches!(
+            first.events().try_recv(),
+            Some(ToastEvent::Dismissed {
+                reason: DismissReason::TimedOut
+            })
+        ));
+
+        // replacing the toast dismisses it and ends its events
+        let second = notifier.show(&toast).unwrap();
+        assert!(matches!(
+            first.events().recv(),
+            Some(ToastEvent::Dismissed {
+                reason: DismissReason::Replaced
+            })
+        ));
+        assert_eq!(first.get_dismiss_reason(), Some(DismissReason::Replaced));
+        assert!(first.events().into_iter().next().is_none());
+        assert!(backend.fail(0, Error::unsupported_os("showing toasts")));
+        assert!(matches!(
+            second.events().recv(),
+            Some(ToastEvent::Failed { .. })
+        ));
+        assert!(backend.toasts().is_empty());
+        assert!(!backend.dismiss(0, DismissReason::UserCanceled));
+    }
+
+    #[test]
+    fn hides_from_another_thread() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+        let mut call = Toast::new().unwrap();
+        call.tag("call").unwrap();
+        let handle = notifier.show(&call).unwrap();
+        let mut missed = Toast::new().unwrap();
+        missed.tag("missed").unwrap();
+        let in_history = notifier.show(&missed).unwrap();
+        assert!(backend.dismiss(1, DismissReason::TimedOut));
+        assert!(!backend.toasts()[1].on_screen);
+
+        // the handle is sent to the thread that learns the call was answered
+        let handle = thread::scope(|scope| {
+            scope
+                .spawn(move || {
+                    handle.hide().unwrap();
+                    handle
+                })
+                .join()
+                .unwrap()
+        });
+        assert!(matches!(
+            handle.events().recv(),
+            Some(ToastEvent::Dismissed {
+                reason: DismissReason::ApplicationHidden
+            })
+        ));
+        in_history.hide().unwrap();
+        assert_eq!(backend.toasts().len(), 1);
+        in_history.remove().unwrap();
+        assert!(backend.toasts().is_empty());
+
+        // untagged toasts are found by the handle, only the handle of the hidden one gets the dismissal
+        let untagged = notifier.show(&Toast::new().unwrap()).unwrap();
+        let other = notifier.show(&Toast::new().unwrap()).unwrap();
+        untagged.hide().unwrap();
+        assert!(matches!(
+            untagged.events().recv(),
+            Some(ToastEvent::Dismissed {
+                reason: DismissReason::ApplicationHidden
+            })
+        ));
+        assert!(other.events().try_recv().is_none());
+        assert_eq!(backend.toasts().len(), 1);
+    }
+
+    #[test]
+    fn purges_expired_toasts() {
+        let clock = Arc::new(ManualClock::default());
+        let backend = Arc::new(MemoryBackend::new().clock(clock.clone()));
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+        let show = |expiration: Option<u64>, expires_on_reboot: bool| {
+            let mut toast = Toast::new().unwrap();
+            if let Some(expiration) = expiration {
+                toast
+                    .expiration(UNIX_EPOCH + Duration::from_secs(expiration))
+                    .unwrap();
+            }
+            toast.expires_on_reboot(expires_on_reboot).unwrap();
+            notifier.show(&toast).unwrap()
+        };
+        let expiring = show(Some(60), false);
+        show(Some(120), false);
+        show(None, true);
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(backend.toasts().len(), 2);
+        assert!(matches!(
+            expiring.events().try_recv(),
+            Some(ToastEvent::Dismissed {
+                reason: DismissReason::Expired
+            })
+        ));
+        backend.reboot();
+        assert_eq!(backend.toasts().len(), 1);
+        clock.advance(Duration::from_secs(60));
+        assert!(backend.toasts().is_empty());
+    }
+
+    #[test]
+    fn updates_bound_progress() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+        let mut toast = Toast::new().unwrap();
+        toast.add_progress(Progress::bound()).unwrap();
+        toast.tag("download").unwrap();
+        toast.group("files").unwrap();
+        toast
+            .data(
+                NotificationData::new()
+                    .value("progressValue", "0")
+                    .value("progressStatus", "Starting...")
+                    .sequence_number(1),
+            )
+            .unwrap();
+        notifier.show(&toast).unwrap();
+        assert!(backend.toasts()[0]
+            .payload
+            .xml
+            .contains(r#"value="{progressValue}""#));
+
+        let update = NotificationData::new()
+            .value("progressValue", "0.5")
+            .sequence_number(3);
+        assert_eq!(
+            notifier.update(toast.get_identity(), &update).unwrap(),
+            UpdateResult::Succeeded
+        );
+        let stale = NotificationData::new()
+            .value("progressValue", "0.2")
+            .sequence_number(2);
+        assert_eq!(
+            notifier.update(toast.get_identity(), &stale).unwrap(),
+            UpdateResult::Failed
+        );
+
+        let data = backend.toasts()[0].payload.data.clone().unwrap();
+        assert_eq!(data.get_value("progressValue"), Some("0.5"));
+        assert_eq!(data.get_value("progressStatus"), Some("Starting..."));
+
+        toast.group("other").unwrap();
+        assert_eq!(
+            notifier.update(toast.get_identity(), &update).unwrap(),
+            UpdateResult::NotificationNotFound
+        );
+    }
+}
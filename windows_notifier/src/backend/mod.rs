@@ -0,0 +1,227 @@
+//! Where shown toasts go, so the same toast can be sent to windows or, in tests, to an in-memory [`MemoryBackend`].
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::error::Result;
+use crate::events::EventSender;
+use crate::identity::ToastIdentity;
+
+mod memory;
+#[cfg(windows)]
+mod winrt;
+
+pub use memory::{MemoryBackend, MemoryToast};
+#[cfg(windows)]
+pub use winrt::WinRtBackend;
+
+/// Shows toasts and changes the ones that are already shown.
+///
+/// The identity passed to [`Backend::update`] and [`Backend::remove`] always has a tag.
+pub trait Backend: Send + Sync {
+    /// Show the toast for `app_id`, replacing the shown toast it has the same identity as.
+    ///
+    /// What happens to the toast afterwards is sent to `events`, which is dropped once no more events can happen,
+    /// even while the returned [`ShownToast`] is kept.
+    /// The returned [`ShownToast`] is passed to [`Backend::hide`] to find the toast again, even if it has no tag.
+    fn show(&self, app_id: &str, payload: &ToastPayload, events: EventSender)
+        -> Result<ShownToast>;
+
+    /// Apply `data` to the shown toast of `app_id` with `identity`.
+    fn update(
+        &self,
+        app_id: &str,
+        identity: &ToastIdentity,
+        data: &NotificationData,
+    ) -> Result<UpdateResult>;
+
+    /// Take the toast of `app_id` that `show` returned `shown` for off the screen, if it is still shown.
+    /// If `shown` is empty the toast is found by `identity`, which then has a tag.
+    /// A toast already in the Action Center stays there.
+    fn hide(&self, app_id: &str, identity: &ToastIdentity, shown: &ShownToast) -> Result<()>;
+
+    /// Remove the toast of `app_id` with `identity`, if it is still there.
+    fn remove(&self, app_id: &str, identity: &ToastIdentity) -> Result<()>;
+
+    /// The toasts of `app_id` in the Action Center, oldest first.
+    fn history(&self, app_id: &str) -> Result<Vec<ToastPayload>>;
+
+    /// Remove all toasts of `app_id` in `group`.
+    fn remove_group(&self, app_id: &str, group: &str) -> Result<()>;
+
+    /// Remove all toasts of `app_id`.
+    fn clear(&self, app_id: &str) -> Result<()>;
+}
+
+/// What a backend keeps of a toast it showed, like the WinRT `ToastNotification`, to find the toast again without a tag.
+#[derive(Clone, Default)]
+pub struct ShownToast(Option<Arc<dyn Any + Send + Sync>>);
+
+impl ShownToast {
+    pub fn new(shown: impl Any + Send + Sync) -> ShownToast {
+        ShownToast(Some(Arc::new(shown)))
+    }
+
+    /// What the backend kept, if it kept a `T`.
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.as_deref()?.downcast_ref()
+    }
+
+    /// Whether the backend kept nothing, like for a handle that was not returned by a show.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl fmt::Debug for ShownToast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShownToast")
+            .field("empty", &self.is_empty())
+            .finish()
+    }
+}
+
+/// Everything a backend needs to show a toast.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct ToastPayload {
+    /// The toast xml, from [`Toast::into_raw`](crate::Toast::into_raw).
+    pub xml: String,
+    /// The tag, group and remote id of the toast.
+    pub identity: ToastIdentity,
+    /// The values of the `{key}` bindings when the toast is shown.
+    pub data: Option<NotificationData>,
+    /// When the toast is removed from the Action Center.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub expiration: Option<SystemTime>,
+    /// Whether the toast is removed from the Action Center when the system restarts.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub expires_on_reboot: bool,
+}
+
+/// The outcome of an update, like the WinRT `NotificationUpdateResult`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpdateResult {
+    Succeeded,
+    /// The toast was found, but the update was not applied, like when its sequence number is too old.
+    Failed,
+    /// There is no shown toast with the tag and group.
+    NotificationNotFound,
+}
+
+/// The values of the `{key}` bindings of a toast, like the progress of a [bound](crate::tags::progress::Progress::bound) progress bar.
+///
+/// ```
+/// # use windows_notifier::backend::NotificationData;
+/// let data = NotificationData::new()
+///     .value("progressValue", "0.6")
+///     .value("progressStatus", "Downloading...")
+///     .sequence_number(2);
+/// assert_eq!(data.get_value("progressValue"), Some("0.6"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
+pub struct NotificationData {
+    values: BTreeMap<String, String>,
+    sequence_number: u32,
+}
+
+impl NotificationData {
+    pub fn new() -> NotificationData {
+        NotificationData::default()
+    }
+
+    /// Set the value of binding `key`.
+    pub fn value(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.values.insert(key.into(), value.into());
+        self
+    }
+
+    /// Updates with a sequence number that is not higher than the one of the shown data are dropped,
+    /// so updates arriving out of order do not show old values. 0 always replaces the data.
+    pub fn sequence_number(mut self, sequence_number: u32) -> Self {
+        self.sequence_number = sequence_number;
+        self
+    }
+
+    /// The value of binding `key`.
+    pub fn get_value(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// All values, ordered by key.
+    pub fn values(&self) -> &BTreeMap<String, String> {
+        &self.values
+    }
+
+    pub fn get_sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+
+    /// Apply an update to this data, following the sequence number rules of [`NotificationData::sequence_number`].
+    pub(crate) fn apply(&mut self, update: &NotificationData) -> UpdateResult {
+        if update.sequence_number != 0 && update.sequence_number <= self.sequence_number {
+            return UpdateResult::Failed;
+        }
+        self.values.extend(update.values.clone());
+        self.sequence_number = update.sequence_number;
+        UpdateResult::Succeeded
+    }
+}
+
+/// The backend for the platform, used by [`Notifier::new`](crate::notifier::Notifier::new).
+pub(crate) fn platform() -> Arc<dyn Backend> {
+    #[cfg(windows)]
+    return Arc::new(WinRtBackend::new());
+
+    #[cfg(not(windows))]
+    return Arc::new(Unsupported);
+}
+
+/// Fails every call, there is no notification system to send toasts to.
+#[cfg(not(windows))]
+struct Unsupported;
+
+#[cfg(not(windows))]
+impl Backend for Unsupported {
+    fn show(&self, _: &str, _: &ToastPayload, _: EventSender) -> Result<ShownToast> {
+        Err(crate::error::Error::unsupported_os("showing toasts"))
+    }
+
+    fn update(&self, _: &str, _: &ToastIdentity, _: &NotificationData) -> Result<UpdateResult> {
+        Err(crate::error::Error::unsupported_os("updating toasts"))
+    }
+
+    fn hide(&self, _: &str, _: &ToastIdentity, _: &ShownToast) -> Result<()> {
+        Err(crate::error::Error::unsupported_os("hiding toasts"))
+    }
+
+    fn remove(&self, _: &str, _: &ToastIdentity) -> Result<()> {
+        Err(crate::error::Error::unsupported_os("removing toasts"))
+    }
+
+    fn history(&self, _: &str) -> Result<Vec<ToastPayload>> {
+        Err(crate::error::Error::unsupported_os(
+            "reading the toast history",
+        ))
+    }
+
+    fn remove_group(&self, _: &str, _: &str) -> Result<()> {
+        Err(crate::error::Error::unsupported_os("removing toasts"))
+    }
+
+    fn clear(&self, _: &str) -> Result<()> {
+        Err(crate::error::Error::unsupported_os("removing toasts"))
+    }
+}
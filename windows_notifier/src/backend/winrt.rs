@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use windows::{
+    core::{ComInterface, IInspectable, HSTRING},
+    Data::Xml::Dom::XmlDocument,
+    Foundation::{Collections::ValueSet, DateTime, IReference, PropertyValue, TypedEventHandler},
+    UI::Notifications::{
+        NotificationUpdateResult, ToastActivatedEventArgs, ToastDismissedEventArgs,
+        ToastFailedEventArgs, ToastNotification, ToastNotificationManager, ToastNotifier,
+    },
+};
+
+use crate::error::Result;
+use crate::events::{DismissReason, EventSender, ToastEvent};
+use crate::identity::ToastIdentity;
+use crate::tags::activation::AfterActivationBehavior;
+use crate::Toast;
+
+use super::{Backend, NotificationData, ShownToast, ToastPayload, UpdateResult};
+
+/// Shows toasts with the WinRT `ToastNotificationManager`.
+#[derive(Debug, Default)]
+pub struct WinRtBackend {
+    /// The notifier of every app id toasts were shown for, created once and reused by every show.
+    notifiers: Mutex<Vec<(String, ToastNotifier)>>,
+    /// The shown toasts with a tag, so `ToastNotifier::Hide` can hide them by their identity.
+    /// A toast is dropped once it is activated, dismissed, failed, replaced or removed.
+    shown: Arc<Mutex<Vec<Shown>>>,
+}
+
+/// A shown toast with a tag.
+#[derive(Debug)]
+struct Shown {
+    app_id: String,
+    identity: ToastIdentity,
+    toast: ToastNotification,
+}
+
+impl WinRtBackend {
+    pub fn new() -> WinRtBackend {
+        WinRtBackend::default()
+    }
+
+    /// Drops the shown toasts of `app_id` matching `forget`.
+    fn forget(&self, app_id: &str, mut forget: impl FnMut(&ToastIdentity) -> bool) {
+        lock(&self.shown).retain(|shown| !(shown.app_id == app_id && forget(&shown.identity)));
+    }
+
+    /// The notifier of `app_id`, created the first time it is needed.
+    fn notifier(&self, app_id: &str) -> Result<ToastNotifier> {
+        let mut notifiers = lock(&self.notifiers);
+        if let Some((_, notifier)) = notifiers.iter().find(|(id, _)| id == app_id) {
+            return Ok(notifier.clone());
+        }
+        let notifier = ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id))?;
+        notifiers.push((app_id.into(), notifier.clone()));
+        Ok(notifier)
+    }
+}
+
+/// Drops `toast` from the shown toasts, once it is no longer on screen.
+fn forget_toast(shown: &Mutex<Vec<Shown>>, toast: &ToastNotification) {
+    lock(shown).retain(|shown| shown.toast != *toast);
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // The lists are only changed by push and retain and the sender is only taken,
+    // a panic can not leave them half changed.
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Backend for WinRtBackend {
+    /// Loads the toast xml into a WinRT `XmlDocument` and shows it.
+    fn show(
+        &self,
+        app_id: &str,
+        payload: &ToastPayload,
+        events: EventSender,
+    ) -> Result<ShownToast> {
+        let doc = XmlDocument::new()?;
+        doc.LoadXml(&HSTRING::from(payload.xml.as_str()))?;
+
+        let notifier = self.notifier(app_id)?;
+        let toast = ToastNotification::CreateToastNotification(&doc)?;
+        let identity = &payload.identity;
+        if let Some(tag) = identity.get_tag() {
+            toast.SetTag(&HSTRING::from(tag))?;
+        }
+        if let Some(group) = identity.get_group() {
+            toast.SetGroup(&HSTRING::from(group))?;
+        }
+        if let Some(remote_id) = identity.get_remote_id() {
+            toast.SetRemoteId(&HSTRING::from(remote_id))?;
+        }
+        if let Some(data) = &payload.data {
+            toast.SetData(&notification_data(data)?)?;
+        }
+        if let Some(expiration) = payload.expiration {
+            let expiration: IReference<DateTime> =
+                PropertyValue::CreateDateTime(to_date_time(expiration))?.cast()?;
+            toast.SetExpirationTime(&expiration)?;
+        }
+        if payload.expires_on_reboot {
+            toast.SetExpiresOnReboot(true)?;
+        }
+        let subscription = Subscription::new(payload, events, Arc::downgrade(&self.shown));
+        subscribe(&toast, subscription)?;
+        notifier.Show(&toast)?;
+
+        if identity.get_tag().is_some() {
+            self.forget(app_id, |shown| shown.replaces(identity));
+            lock(&self.shown).push(Shown {
+                app_id: app_id.into(),
+                identity: identity.clone(),
+                toast: toast.clone(),
+            });
+        }
+        Ok(ShownToast::new(toast))
+    }
+
+    fn update(
+        &self,
+        app_id: &str,
+        identity: &ToastIdentity,
+        data: &NotificationData,
+    ) -> Result<UpdateResult> {
+        let notifier = self.notifier(app_id)?;
+        let data = notification_data(data)?;
+        let tag = HSTRING::from(identity.get_tag().unwrap_or_default());
+        let result = match identity.get_group() {
+            Some(group) => notifier.UpdateWithTagAndGroup(&data, &tag, &HSTRING::from(group))?,
+            None => notifier.UpdateWithTag(&data, &tag)?,
+        };
+        Ok(match result {
+            NotificationUpdateResult::Succeeded => UpdateResult::Succeeded,
+            NotificationUpdateResult::NotificationNotFound => UpdateResult::NotificationNotFound,
+            _ => UpdateResult::Failed,
+        })
+    }
+
+    fn history(&self, app_id: &str) -> Result<Vec<ToastPayload>> {
+        let toasts =
+            ToastNotificationManager::History()?.GetHistoryWithId(&HSTRING::from(app_id))?;
+        let mut history = Vec::new();
+        for index in 0..toasts.Size()? {
+            let toast = toasts.GetAt(index)?;
+            let mut identity = ToastIdentity::default();
+            identity.tag = non_empty(toast.Tag()?);
+            identity.group = non_empty(toast.Group()?);
+            identity.remote_id = non_empty(toast.RemoteId()?);
+            history.push(ToastPayload {
+                xml: toast.Content()?.GetXml()?.to_string(),
+                identity,
+                // toasts shown without data have none
+                data: toast.Data().ok().map(|data| read_data(&data)).transpose()?,
+                expiration: toast
+                    .ExpirationTime()
+                    .and_then(|expiration| expiration.Value())
+                    .ok()
+                    .map(from_date_time),
+                expires_on_reboot: toast.ExpiresOnReboot()?,
+            });
+        }
+        Ok(history)
+    }
+
+    fn remove_group(&self, app_id: &str, group: &str) -> Result<()> {
+        self.forget(app_id, |identity| identity.get_group() == Some(group));
+        ToastNotificationManager::History()?
+            .RemoveGroupWithId(&HSTRING::from(group), &HSTRING::from(app_id))?;
+        Ok(())
+    }
+
+    fn clear(&self, app_id: &str) -> Result<()> {
+        self.forget(app_id, |_| true);
+        ToastNotificationManager::History()?.ClearWithId(&HSTRING::from(app_id))?;
+        Ok(())
+    }
+
+    fn hide(&self, app_id: &str, identity: &ToastIdentity, shown: &ShownToast) -> Result<()> {
+        let toast = match shown.get::<ToastNotification>() {
+            Some(toast) => toast.clone(),
+            None => {
+                let shown = lock(&self.shown);
+                let found = shown
+                    .iter()
+                    .find(|shown| shown.app_id == app_id && shown.identity.replaces(identity));
+                match found {
+                    Some(shown) => shown.toast.clone(),
+                    None => return Ok(()),
+                }
+            }
+        };
+        forget_toast(&self.shown, &toast);
+        self.notifier(app_id)?.Hide(&toast)?;
+        Ok(())
+    }
+
+    fn remove(&self, app_id: &str, identity: &ToastIdentity) -> Result<()> {
+        self.forget(app_id, |shown| shown.replaces(identity));
+        let history = ToastNotificationManager::History()?;
+        // Toasts without a group are in the empty group.
+        history.RemoveGroupedTagWithId(
+            &HSTRING::from(identity.get_tag().unwrap_or_default()),
+            &HSTRING::from(identity.get_group().unwrap_or_default()),
+            &HSTRING::from(app_id),
+        )?;
+        Ok(())
+    }
+}
+
+fn notification_data(
+    data: &NotificationData,
+) -> Result<windows::UI::Notifications::NotificationData> {
+    let notification_data = windows::UI::Notifications::NotificationData::new()?;
+    notification_data.SetSequenceNumber(data.get_sequence_number())?;
+    let values = notification_data.Values()?;
+    for (key, value) in data.values() {
+        values.Insert(&HSTRING::from(key.as_str()), &HSTRING::from(value.as_str()))?;
+    }
+    Ok(notification_data)
+}
+
+fn read_data(data: &windows::UI::Notifications::NotificationData) -> Result<NotificationData> {
+    let mut read = NotificationData::new().sequence_number(data.SequenceNumber()?);
+    let values = data.Values()?.First()?;
+    while values.HasCurrent()? {
+        let pair = values.Current()?;
+        read = read.value(&pair.Key()?.to_string(), pair.Value()?.to_string());
+        values.MoveNext()?;
+    }
+    Ok(read)
+}
+
+fn non_empty(value: HSTRING) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// WinRT counts time in 100 nanosecond ticks since 1601-01-01.
+const UNIX_EPOCH_TICKS: i64 = 116_444_736_000_000_000;
+
+fn to_date_time(time: SystemTime) -> DateTime {
+    let ticks = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_nanos() / 100) as i64,
+        Err(err) => -((err.duration().as_nanos() / 100) as i64),
+    };
+    DateTime {
+        UniversalTime: UNIX_EPOCH_TICKS + ticks,
+    }
+}
+
+fn from_date_time(time: DateTime) -> SystemTime {
+    let ticks = time.UniversalTime - UNIX_EPOCH_TICKS;
+    let since = Duration::from_nanos(ticks.unsigned_abs() * 100);
+    if ticks >= 0 {
+        UNIX_EPOCH + since
+    } else {
+        UNIX_EPOCH - since
+    }
+}
+
+/// The events of one shown toast. The sender is dropped with the last event of the toast,
+/// as the handlers live as long as the `ToastNotification`, which the [`ShownToast`] keeps.
+struct Subscription {
+    events: Mutex<Option<EventSender>>,
+    /// The arguments of the buttons that keep the toast after activating it.
+    pending_updates: Vec<String>,
+    /// The shown toasts of the backend, weak as the shown toasts keep the handlers.
+    shown: Weak<Mutex<Vec<Shown>>>,
+}
+
+impl Subscription {
+    fn new(
+        payload: &ToastPayload,
+        events: EventSender,
+        shown: Weak<Mutex<Vec<Shown>>>,
+    ) -> Arc<Subscription> {
+        let pending_updates = Toast::from_xml(&payload.xml)
+            .map(|toast| {
+                toast
+                    .actions()
+                    .iter()
+                    .filter(|action| {
+                        action.get_after_activation_behavior()
+                            == Some(AfterActivationBehavior::PendingUpdate)
+                    })
+                    .map(|action| action.get_arguments().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Arc::new(Subscription {
+            events: Mutex::new(Some(events)),
+            pending_updates,
+            shown,
+        })
+    }
+
+    /// Sends `event` of `toast`, dropping the sender and forgetting the toast if it is gone afterwards.
+    /// A toast that timed out is still in the Action Center, where it can be activated.
+    fn send(&self, toast: Option<&ToastNotification>, event: ToastEvent) {
+        let last = match &event {
+            ToastEvent::Activated { arguments, .. } => !self.pending_updates.contains(arguments),
+            ToastEvent::Dismissed { reason } => *reason != DismissReason::TimedOut,
+            ToastEvent::Failed { .. } => true,
+        };
+        let mut events = lock(&self.events);
+        if let Some(sender) = events.as_ref() {
+            sender.send(event);
+        }
+        if last {
+            *events = None;
+            if let (Some(toast), Some(shown)) = (toast, self.shown.upgrade()) {
+                forget_toast(&shown, toast);
+            }
+        }
+    }
+}
+
+/// Forwards the activated, dismissed and failed events of `toast` to `subscription`.
+fn subscribe(toast: &ToastNotification, subscription: Arc<Subscription>) -> Result<()> {
+    let activated = subscription.clone();
+    toast.Activated(&TypedEventHandler::<ToastNotification, IInspectable>::new(
+        move |toast, args| {
+            if let Some(args) = args {
+                let args: ToastActivatedEventArgs = args.cast()?;
+                activated.send(
+                    toast.as_ref(),
+                    ToastEvent::Activated {
+                        arguments: args.Arguments()?.to_string(),
+                        user_inputs: read_inputs(&args.UserInput()?)?,
+                    },
+                );
+            }
+            Ok(())
+        },
+    ))?;
+
+    let dismissed = subscription.clone();
+    toast.Dismissed(&TypedEventHandler::<
+        ToastNotification,
+        ToastDismissedEventArgs,
+    >::new(move |toast, args| {
+        if let Some(args) = args {
+            let reason = DismissReason::from_windows(args.Reason()?.0);
+            dismissed.send(toast.as_ref(), ToastEvent::Dismissed { reason });
+        }
+        Ok(())
+    }))?;
+
+    toast.Failed(
+        &TypedEventHandler::<ToastNotification, ToastFailedEventArgs>::new(move |toast, args| {
+            if let Some(args) = args {
+                subscription.send(
+                    toast.as_ref(),
+                    ToastEvent::Failed {
+                        error: windows::core::Error::from(args.ErrorCode()?).into(),
+                    },
+                );
+            }
+            Ok(())
+        }),
+    )?;
+    Ok(())
+}
+
+/// The values of the inputs, which are all strings for toasts.
+fn read_inputs(inputs: &ValueSet) -> windows::core::Result<HashMap<String, String>> {
+    let mut read = HashMap::new();
+    let pairs = inputs.First()?;
+    while pairs.HasCurrent()? {
+        let pair = pairs.Current()?;
+        let value: IReference<HSTRING> = pair.Value()?.cast()?;
+        read.insert(pair.Key()?.to_string(), value.Value()?.to_string());
+        pairs.MoveNext()?;
+    }
+    Ok(read)
+}
@@ -0,0 +1,365 @@
+//! Toasts written as JSON, TOML or YAML definitions, so their content can live in config files.
+//!
+//! A definition mirrors the toast xml, with the attributes in camelCase:
+//!
+//! ```json
+//! {
+//!     "scenario": "reminder",
+//!     "visual": { "binding": { "children": [{ "text": { "text": "Standup in 5 minutes" } }] } },
+//!     "actions": { "actions": [{ "content": "Join", "arguments": "action=join", "activationType": "background" }] }
+//! }
+//! ```
+//!
+//! YAML writes enum variants like the `text` binding child as tags, `- !text { text: Standup }`.
+//!
+//! Next to the xml content, a definition holds what the backend gets when the toast is shown:
+//! the `identity` with its `tag` and `group`, the `data` of its bindings, the `expiration` and `expiresOnReboot`.
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::backend::NotificationData;
+use crate::identity::ToastIdentity;
+use crate::raw::Extra;
+use crate::tags::actions::Actions;
+use crate::tags::activation::ActivationType;
+use crate::tags::audio::Audio;
+use crate::tags::commands::Command;
+use crate::tags::header::Header;
+use crate::tags::toast::{Duration, Scenarios};
+use crate::tags::visual::Visual;
+use crate::Toast;
+
+/// The content of a [`Toast`] without its typestate.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Definition {
+    duration: Option<Duration>,
+    launch: Option<String>,
+    activation_type: Option<ActivationType>,
+    target_application_pfn: Option<String>,
+    scenario: Option<Scenarios>,
+    #[serde(default)]
+    use_button_style: bool,
+    #[serde(default, with = "iso8601", skip_serializing_if = "Option::is_none")]
+    display_timestamp: Option<SystemTime>,
+    #[serde(default)]
+    visual: Visual,
+    #[serde(default, skip_serializing_if = "Actions::is_empty")]
+    actions: Actions,
+    audio: Option<Audio>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    commands: Vec<Command>,
+    header: Option<Header>,
+    #[serde(default, skip_serializing_if = "Extra::is_empty")]
+    extra: Extra,
+    #[serde(default, skip_serializing_if = "is_default")]
+    identity: ToastIdentity,
+    data: Option<NotificationData>,
+    #[serde(default, with = "iso8601", skip_serializing_if = "Option::is_none")]
+    expiration: Option<SystemTime>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    expires_on_reboot: bool,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+impl<S, C> Serialize for Toast<S, C> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> std::result::Result<Z::Ok, Z::Error> {
+        Definition {
+            duration: self.duration,
+            launch: self.launch.clone(),
+            activation_type: self.activation_type,
+            target_application_pfn: self.target_application_pfn.clone(),
+            scenario: self.scenario,
+            use_button_style: self.use_button_style,
+            display_timestamp: self.display_timestamp,
+            visual: self.visual.clone(),
+            actions: self.actions.clone(),
+            audio: self.audio.clone(),
+            commands: self.commands.clone(),
+            header: self.header.clone(),
+            extra: self.extra.clone(),
+            identity: self.identity.clone(),
+            data: self.data.clone(),
+            expiration: self.expiration,
+            expires_on_reboot: self.expires_on_reboot,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Like [`Toast::from_xml`], a deserialized toast is not checked by the typestate,
+/// use [`Toast::validate`] or one of the loaders, which reject toasts windows would not show.
+impl<'de> Deserialize<'de> for Toast<()> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let definition = Definition::deserialize(deserializer)?;
+        let mut toast = Toast::new().map_err(serde::de::Error::custom)?;
+        toast.duration = definition.duration;
+        toast.launch = definition.launch;
+        toast.activation_type = definition.activation_type;
+        toast.target_application_pfn = definition.target_application_pfn;
+        toast.scenario = definition.scenario;
+        toast.use_button_style = definition.use_button_style;
+        toast.display_timestamp = definition.display_timestamp;
+        toast.visual = definition.visual;
+        toast.actions = definition.actions;
+        toast.audio = definition.audio;
+        toast.commands = definition.commands;
+        toast.header = definition.header;
+        toast.extra = definition.extra;
+        // The tag and group are checked like the builder checks them.
+        let identity = definition.identity;
+        if let Some(tag) = &identity.tag {
+            toast.tag(tag).map_err(serde::de::Error::custom)?;
+        }
+        if let Some(group) = &identity.group {
+            toast.group(group).map_err(serde::de::Error::custom)?;
+        }
+        toast.identity.remote_id = identity.remote_id;
+        toast.data = definition.data;
+        toast.expiration = definition.expiration;
+        toast.expires_on_reboot = definition.expires_on_reboot;
+        Ok(toast)
+    }
+}
+
+/// Timestamps are written in ISO 8601, like in the xml.
+mod iso8601 {
+    use std::time::SystemTime;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::utils::time::{format_iso8601, parse_iso8601};
+
+    pub fn serialize<Z: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: Z,
+    ) -> Result<Z::Ok, Z::Error> {
+        match time {
+            Some(time) => serializer.serialize_str(&format_iso8601(*time)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|time| {
+                parse_iso8601(&time).ok_or_else(|| {
+                    serde::de::Error::custom(format!("{:?} is not an ISO 8601 timestamp", time))
+                })
+            })
+            .transpose()
+    }
+}
+
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+mod load {
+    use std::path::Path;
+
+    use crate::error::{Error, Result};
+    use crate::validate::Severity;
+    use crate::Toast;
+
+    impl Toast<()> {
+        /// Load a toast definition from a `.json`, `.toml`, `.yaml` or `.yml` file.
+        ///
+        /// Each format needs its cargo feature, `json`, `toml` or `yaml`.
+        pub fn load(path: impl AsRef<Path>) -> Result<Toast<()>> {
+            let path = path.as_ref();
+            let content =
+                std::fs::read_to_string(path).map_err(|err| Error::load(path.display(), err))?;
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            let toast = match extension {
+                #[cfg(feature = "json")]
+                "json" => {
+                    serde_json::from_str(&content).map_err(|err| Error::load(path.display(), err))
+                }
+                #[cfg(feature = "toml")]
+                "toml" => toml::from_str(&content).map_err(|err| Error::load(path.display(), err)),
+                #[cfg(feature = "yaml")]
+                "yaml" | "yml" => {
+                    serde_yaml::from_str(&content).map_err(|err| Error::load(path.display(), err))
+                }
+                _ => {
+                    return Err(Error::Unsupported {
+                        backend: "the definition loader".into(),
+                        operation: format!("loading .{} files", extension),
+                    })
+                }
+            }?;
+            checked(toast)
+        }
+
+        /// Load a toast definition from JSON.
+        #[cfg(feature = "json")]
+        pub fn from_json(json: &str) -> Result<Toast<()>> {
+            checked(serde_json::from_str(json).map_err(|err| Error::load("json", err))?)
+        }
+
+        /// Load a toast definition from TOML.
+        #[cfg(feature = "toml")]
+        pub fn from_toml(toml: &str) -> Result<Toast<()>> {
+            checked(toml::from_str(toml).map_err(|err| Error::load("toml", err))?)
+        }
+
+        /// Load a toast definition from YAML.
+        #[cfg(feature = "yaml")]
+        pub fn from_yaml(yaml: &str) -> Result<Toast<()>> {
+            checked(serde_yaml::from_str(yaml).map_err(|err| Error::load("yaml", err))?)
+        }
+    }
+
+    /// Rejects a loaded toast that breaks a rule windows enforces, so a bad definition fails at startup.
+    fn checked(toast: Toast<()>) -> Result<Toast<()>> {
+        let error = toast
+            .validate()
+            .into_iter()
+            .find(|diagnostic| diagnostic.severity == Severity::Error);
+        match error {
+            Some(diagnostic) => Err(match diagnostic.limit {
+                Some(limit) => Error::limit_exceeded(diagnostic.path, limit),
+                None => Error::invalid_argument(diagnostic.path, diagnostic.message),
+            }),
+            None => Ok(toast),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json", feature = "toml", feature = "yaml"))]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::backend::NotificationData;
+    use crate::error::Error;
+    use crate::identity::ToastIdentity;
+    use crate::Toast;
+
+    #[test]
+    fn loads_every_format() {
+        let json = r#"{
+            "scenario": "reminder",
+            "displayTimestamp": "2017-04-15T19:45:00Z",
+            "visual": { "binding": { "children": [{ "text": { "text": "Standup" } }] } },
+            "actions": { "actions": [{ "content": "Join", "arguments": "join", "activationType": "background" }] },
+            "audio": { "src": "Reminder" }
+        }"#;
+        let toml = r#"
+            scenario = "reminder"
+            displayTimestamp = "2017-04-15T19:45:00Z"
+            [[visual.binding.children]]
+            text = { text = "Standup" }
+            [[actions.actions]]
+            content = "Join"
+            arguments = "join"
+            activationType = "background"
+            [audio]
+            src = "Reminder"
+        "#;
+        let yaml = r#"
+            scenario: reminder
+            displayTimestamp: 2017-04-15T19:45:00Z
+            visual:
+              binding:
+                children:
+                  - !text { text: Standup }
+            actions:
+              actions:
+                - { content: Join, arguments: join, activationType: background }
+            audio:
+              src: Reminder
+        "#;
+
+        let raw = Toast::from_json(json).unwrap().into_raw().unwrap();
+        assert_eq!(Toast::from_toml(toml).unwrap().into_raw().unwrap(), raw);
+        assert_eq!(Toast::from_yaml(yaml).unwrap().into_raw().unwrap(), raw);
+        assert!(
+            raw.contains(r#"<toast scenario="reminder" displayTimestamp="2017-04-15T19:45:00Z">"#)
+        );
+
+        let toast = Toast::from_json(json).unwrap();
+        let json = serde_json::to_string(&toast).unwrap();
+        assert_eq!(Toast::from_json(&json).unwrap().into_raw().unwrap(), raw);
+    }
+
+    #[test]
+    fn rejects_broken_definitions() {
+        let actions = [r#"{ "content": "a", "arguments": "a" }"#; 6].join(",");
+        let json = format!(r#"{{ "actions": {{ "actions": [{}] }} }}"#, actions);
+        let err = Toast::from_json(&json).err().unwrap();
+        assert!(
+            matches!(err, Error::LimitExceeded { path, limit: 5 } if path == "toast/actions/action[6]")
+        );
+        let json = r#"{ "actions": { "actions": [{ "content": "a", "arguments": "a", "hintInputId": "reply" }] } }"#;
+        assert!(matches!(
+            Toast::from_json(json).err().unwrap(),
+            Error::InvalidArgument { path, .. } if path == "toast/actions/action[1]"
+        ));
+        let json = format!(r#"{{ "identity": {{ "tag": "{}" }} }}"#, "a".repeat(65));
+        assert!(matches!(
+            Toast::from_json(&json).err().unwrap(),
+            Error::Load { .. }
+        ));
+
+        assert!(matches!(
+            Toast::load("missing.json").err().unwrap(),
+            Error::Load { .. }
+        ));
+        let path = std::env::temp_dir().join(format!("toast-{}.ini", std::process::id()));
+        std::fs::write(&path, "[toast]\nlaunch = open\n").unwrap();
+        let err = Toast::load(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            err,
+            Error::Unsupported { operation, .. } if operation == "loading .ini files"
+        ));
+    }
+
+    #[test]
+    fn keeps_what_is_not_in_the_xml() {
+        let mut toast = Toast::new().unwrap();
+        toast.title("Downloading").unwrap();
+        toast
+            .identity(
+                ToastIdentity::new("download-7")
+                    .unwrap()
+                    .group("downloads")
+                    .unwrap()
+                    .remote_id("7"),
+            )
+            .unwrap();
+        toast
+            .data(
+                NotificationData::new()
+                    .value("progressValue", "0.6")
+                    .sequence_number(2),
+            )
+            .unwrap();
+        toast
+            .expiration(UNIX_EPOCH + Duration::from_secs(1_492_285_500))
+            .unwrap();
+        toast.expires_on_reboot(true).unwrap();
+
+        let json = serde_json::to_string(&toast).unwrap();
+        assert!(json.contains(r#""expiration":"2017-04-15T19:45:00Z""#));
+        let yaml = serde_yaml::to_string(&toast).unwrap();
+        for loaded in [
+            Toast::from_json(&json).unwrap(),
+            Toast::from_yaml(&yaml).unwrap(),
+        ] {
+            assert_eq!(loaded.get_identity(), toast.get_identity());
+            assert_eq!(loaded.get_data(), toast.get_data());
+            assert_eq!(loaded.get_expiration(), toast.get_expiration());
+            assert!(loaded.is_expires_on_reboot());
+            assert_eq!(loaded.into_raw().unwrap(), toast.into_raw().unwrap());
+        }
+
+        let plain = serde_json::to_string(&Toast::new().unwrap()).unwrap();
+        assert!(!plain.contains("identity") && !plain.contains("expiresOnReboot"));
+    }
+}
@@ -1,25 +1,271 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
+
+/// Everything that can go wrong while building, showing or handling a toast.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Error {
-    XmlErr(XmlErr),
-    Windows(windows::core::Error),
+    /// A value given to the builder can not be used, `path` is the element or attribute it was for, like `toast/visual/binding/text/@id`.
+    InvalidArgument { path: String, message: String },
+    /// The toast would have more of an element than windows shows, `path` is the first element over the limit.
+    LimitExceeded { path: String, limit: usize },
+    /// The toast xml could not be written or read.
+    Xml(XmlErr),
+    /// The toast was built fine, but this OS or backend has no way of doing `operation`.
+    Unsupported { backend: String, operation: String },
+    /// The backend failed to deliver the toast.
+    Delivery {
+        backend: String,
+        source: Arc<dyn StdError + Send + Sync>,
+    },
+    /// A [template](crate::template::ToastTemplate) was rendered without a value for these placeholders.
+    MissingVariables(Vec<String>),
+    /// A [localized](crate::Toast::localize) toast uses messages that are in none of the `locales`.
+    MissingMessages {
+        locales: Vec<String>,
+        ids: Vec<String>,
+    },
+    /// The arguments the app was activated with could not be read.
+    ActivationParse { input: String, message: String },
+    /// A toast definition could not be loaded from `path`.
+    Load {
+        path: String,
+        source: Arc<dyn StdError + Send + Sync>,
+    },
+    /// The [scheduled](crate::schedule) toasts could not be read from or written to `path`.
+    Store {
+        path: String,
+        source: Arc<dyn StdError + Send + Sync>,
+    },
+    /// The [delivery queue](crate::queue::DeliveryQueue) was full, so the toast was not queued or was dropped for a newer one.
+    QueueFull { capacity: usize },
+    /// The [delivery queue](crate::queue::DeliveryQueue) was shut down before the toast was queued,
+    /// or its worker stopped before showing it because the backend panicked.
+    QueueClosed,
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+impl Error {
+    pub(crate) fn invalid_argument(path: impl Into<String>, message: impl Into<String>) -> Error {
+        Error::InvalidArgument {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn limit_exceeded(path: impl Into<String>, limit: usize) -> Error {
+        Error::LimitExceeded {
+            path: path.into(),
+            limit,
+        }
+    }
+
+    /// `operation` is not supported on the current OS.
+    pub(crate) fn unsupported_os(operation: impl Into<String>) -> Error {
+        Error::Unsupported {
+            backend: std::env::consts::OS.into(),
+            operation: operation.into(),
+        }
+    }
+
+    #[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+    pub(crate) fn load(
+        path: impl fmt::Display,
+        source: impl StdError + Send + Sync + 'static,
+    ) -> Error {
+        Error::Load {
+            path: path.to_string(),
+            source: Arc::new(source),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn store(
+        path: impl fmt::Display,
+        source: impl StdError + Send + Sync + 'static,
+    ) -> Error {
+        Error::Store {
+            path: path.to_string(),
+            source: Arc::new(source),
+        }
+    }
+
+    /// Wraps an error from a backend that failed to deliver a toast.
+    pub fn delivery(
+        backend: impl Into<String>,
+        source: impl StdError + Send + Sync + 'static,
+    ) -> Error {
+        Error::Delivery {
+            backend: backend.into(),
+            source: Arc::new(source),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidArgument { path, message } => {
+                write!(f, "invalid argument for {}: {}", path, message)
+            }
+            Error::LimitExceeded { path, limit } => {
+                write!(f, "{} is over the limit of {}", path, limit)
+            }
+            Error::Xml(err) => write!(f, "{}", err),
+            Error::Unsupported { backend, operation } => {
+                write!(f, "{} is not supported on {}", operation, backend)
+            }
+            Error::Delivery { backend, .. } => {
+                write!(f, "the {} backend failed to deliver the toast", backend)
+            }
+            Error::MissingVariables(names) => {
+                write!(f, "missing template variables: {}", names.join(", "))
+            }
+            Error::MissingMessages { locales, ids } => write!(
+                f,
+                "no message for {} in {}",
+                ids.join(", "),
+                locales.join(", ")
+            ),
+            Error::ActivationParse { input, message } => {
+                write!(f, "could not parse activation {:?}: {}", input, message)
+            }
+            Error::Load { path, source } => {
+                write!(f, "could not load the toast from {}: {}", path, source)
+            }
+            Error::Store { path, source } => {
+                write!(
+                    f,
+                    "could not store the scheduled toasts in {}: {}",
+                    path, source
+                )
+            }
+            Error::QueueFull { capacity } => {
+                write!(f, "the delivery queue of {} toasts is full", capacity)
+            }
+            Error::QueueClosed => write!(f, "the delivery queue is shut down"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Xml(err) => Some(err),
+            Error::Delivery { source, .. }
+            | Error::Load { source, .. }
+            | Error::Store { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum XmlErr {
-    InvatedArg(String),
+    /// The toast could not be written out as xml.
+    Serialize(String),
+    /// The xml given to [`crate::Toast::from_xml`] could not be read.
+    Parse(String),
+}
+
+impl fmt::Display for XmlErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XmlErr::Serialize(message) => write!(f, "could not write the toast xml: {}", message),
+            XmlErr::Parse(message) => write!(f, "could not read the toast xml: {}", message),
+        }
+    }
 }
 
+impl StdError for XmlErr {}
+
 impl From<XmlErr> for Error {
     fn from(err: XmlErr) -> Self {
-        Error::XmlErr(err)
+        Error::Xml(err)
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(err: quick_xml::Error) -> Self {
+        Error::Xml(XmlErr::Serialize(err.to_string()))
     }
 }
 
+#[cfg(windows)]
 impl From<windows::core::Error> for Error {
     fn from(err: windows::core::Error) -> Self {
-        Error::Windows(err)
+        Error::delivery("windows", err)
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::error::Error as StdError;
+    use std::io;
+
+    use crate::error::{Error, XmlErr};
+    use crate::tags::text::Text;
+    use crate::Toast;
+
+    #[test]
+    fn displays_paths_and_sources() {
+        let mut toast = Toast::new().unwrap();
+        for i in 1..=2 {
+            toast.add_text(Text::new(i.to_string())).unwrap();
+        }
+        let err = toast.add_text(Text::new("3")).unwrap_err();
+        assert!(matches!(err, Error::LimitExceeded { limit: 3, .. }));
+        assert_eq!(
+            err.to_string(),
+            "toast/visual/binding/text[4] is over the limit of 3"
+        );
+        assert!(err.source().is_none());
+
+        let err = Error::invalid_argument("toast/visual/binding/text/@id", "id 0 is reserved");
+        assert_eq!(
+            err.to_string(),
+            "invalid argument for toast/visual/binding/text/@id: id 0 is reserved"
+        );
+
+        let Err(err) = Toast::from_xml("<tost/>") else {
+            panic!("tost is not a toast");
+        };
+        assert_eq!(
+            err.to_string(),
+            "could not read the toast xml: expected a toast element, found tost"
+        );
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<XmlErr>(),
+            Some(&XmlErr::Parse(
+                "expected a toast element, found tost".into()
+            ))
+        );
+
+        let err = Error::delivery("memory", io::Error::other("no session"));
+        assert_eq!(
+            err.to_string(),
+            "the memory backend failed to deliver the toast"
+        );
+        assert_eq!(err.source().unwrap().to_string(), "no session");
+
+        let err = Error::unsupported_os("showing toasts");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "showing toasts is not supported on {}",
+                std::env::consts::OS
+            )
+        );
+        assert_eq!(
+            Error::MissingVariables(vec!["name".into(), "count".into()]).to_string(),
+            "missing template variables: name, count"
+        );
+        assert_eq!(
+            Error::QueueFull { capacity: 4 }.to_string(),
+            "the delivery queue of 4 toasts is full"
+        );
+    }
+}
@@ -0,0 +1,517 @@
+//! What happens to a toast after it is shown: the user activating or dismissing it, or the system failing to show it.
+//!
+//! Every [`ToastHandle`](crate::identity::ToastHandle) has the events of its toast:
+//!
+//! ```
+//! # use std::collections::HashMap;
+//! # use std::sync::Arc;
+//! # use windows_notifier::{backend::MemoryBackend, events::ToastEvent, notifier::Notifier, Toast};
+//! let backend = Arc::new(MemoryBackend::new());
+//! let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+//! let handle = notifier.show(&Toast::new()?)?;
+//!
+//! // the backend delivers what the user did
+//! let inputs = HashMap::from([("reply".to_string(), "On my way".to_string())]);
+//! backend.activate(0, "action=reply", inputs);
+//!
+//! let Some(ToastEvent::Activated { arguments, user_inputs }) = handle.events().try_recv() else {
+//!     panic!("the toast was activated");
+//! };
+//! assert_eq!(arguments, "action=reply");
+//! assert_eq!(user_inputs["reply"], "On my way");
+//! # Ok::<(), windows_notifier::error::Error>(())
+//! ```
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::utils::blocking::{oneshot, Blocking, Canceled, Completer};
+use crate::utils::timer;
+
+/// Why a toast was dismissed, the same for every backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum DismissReason {
+    /// The user closed the toast.
+    UserCanceled,
+    /// The app hid or removed the toast.
+    ApplicationHidden,
+    /// The toast was shown for its duration, a windows toast then moves to the Action Center.
+    TimedOut,
+    /// The [expiration time](crate::Toast::expiration) of the toast passed.
+    Expired,
+    /// A toast with the same [identity](crate::Toast::identity) was shown in its place.
+    Replaced,
+    /// The app had more toasts than the [history](crate::history) keeps, and this was the oldest.
+    HistoryFull,
+    /// The backend reported a reason this crate does not know.
+    Unknown,
+}
+
+impl DismissReason {
+    /// The reason of a WinRT `ToastDismissalReason`.
+    ///
+    /// | `ToastDismissalReason` | reason |
+    /// |---|---|
+    /// | 0 `UserCanceled` | [`UserCanceled`](DismissReason::UserCanceled) |
+    /// | 1 `ApplicationHidden` | [`ApplicationHidden`](DismissReason::ApplicationHidden) |
+    /// | 2 `TimedOut` | [`TimedOut`](DismissReason::TimedOut) |
+    /// | anything else | [`Unknown`](DismissReason::Unknown) |
+    pub fn from_windows(reason: i32) -> DismissReason {
+        match reason {
+            0 => DismissReason::UserCanceled,
+            1 => DismissReason::ApplicationHidden,
+            2 => DismissReason::TimedOut,
+            _ => DismissReason::Unknown,
+        }
+    }
+
+    /// The reason of a freedesktop `NotificationClosed` signal.
+    ///
+    /// | `NotificationClosed` reason | reason |
+    /// |---|---|
+    /// | 1 expired | [`TimedOut`](DismissReason::TimedOut) |
+    /// | 2 dismissed by the user | [`UserCanceled`](DismissReason::UserCanceled) |
+    /// | 3 closed by `CloseNotification` | [`ApplicationHidden`](DismissReason::ApplicationHidden) |
+    /// | 4 undefined, and anything else | [`Unknown`](DismissReason::Unknown) |
+    ///
+    /// A freedesktop notification expires when its display timeout runs out, like a windows toast that times out,
+    /// so it is [`TimedOut`](DismissReason::TimedOut) and not [`Expired`](DismissReason::Expired).
+    pub fn from_freedesktop(reason: u32) -> DismissReason {
+        match reason {
+            1 => DismissReason::TimedOut,
+            2 => DismissReason::UserCanceled,
+            3 => DismissReason::ApplicationHidden,
+            _ => DismissReason::Unknown,
+        }
+    }
+}
+
+/// Something that happened to a shown toast.
+#[derive(Debug, Clone)]
+pub enum ToastEvent {
+    /// The user clicked the toast or one of its actions.
+    Activated {
+        /// The arguments of the action, or the launch arguments of the toast.
+        arguments: String,
+        /// The values of the [inputs](crate::tags::input::Input) by their id,
+        /// the text of text inputs and the selection id of selection inputs.
+        user_inputs: HashMap<String, String>,
+    },
+    /// The toast left the screen without being activated.
+    Dismissed { reason: DismissReason },
+    /// The toast could not be shown.
+    Failed { error: Error },
+}
+
+/// How a toast ended, what [`ToastEvents::wait`] returns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToastOutcome {
+    /// The user clicked the toast or one of its actions.
+    Activated {
+        /// The arguments of the action, or the launch arguments of the toast.
+        arguments: String,
+        /// The values of the inputs by their id.
+        user_inputs: HashMap<String, String>,
+    },
+    /// The toast left the screen without being activated.
+    Dismissed { reason: DismissReason },
+    /// The user did not answer within the timeout, the toast may still be shown.
+    Unanswered,
+    /// The events ended without an answer, like when a backend that does not report
+    /// [`DismissReason::Replaced`] replaced the toast.
+    Closed,
+}
+
+/// Where a backend sends the events of one shown toast.
+#[derive(Debug, Clone)]
+pub struct EventSender {
+    // Dropped before `waiting`, so the events are disconnected before the waiting is canceled.
+    sender: Sender<ToastEvent>,
+    /// Shared by the clones, the last one to be dropped cancels a [`ToastEvents::wait_async`].
+    waiting: Arc<CancelOnDrop>,
+}
+
+/// The [`ToastEvents::wait_async`] of a toast, completed with the next event by the sender,
+/// with `None` by the timer or canceled once the sender is dropped.
+#[derive(Default)]
+struct Waiting {
+    completer: Option<Completer<Option<ToastEvent>>>,
+}
+
+impl fmt::Debug for Waiting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Waiting")
+            .field("waiting", &self.completer.is_some())
+            .finish()
+    }
+}
+
+fn lock(waiting: &Mutex<Waiting>) -> MutexGuard<'_, Waiting> {
+    // The completer is only taken or replaced, a panic can not leave it half changed.
+    waiting.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Debug)]
+struct CancelOnDrop(Arc<Mutex<Waiting>>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        // Dropping the completer completes the wait with `Canceled`.
+        lock(&self.0).completer = None;
+    }
+}
+
+impl EventSender {
+    /// A sender and the events it sends to.
+    pub fn channel() -> (EventSender, ToastEvents) {
+        let (sender, receiver) = mpsc::channel();
+        let waiting = Arc::new(Mutex::new(Waiting::default()));
+        let events = ToastEvents {
+            receiver,
+            dismissed: Cell::new(None),
+            waiting: waiting.clone(),
+        };
+        let sender = EventSender {
+            sender,
+            waiting: Arc::new(CancelOnDrop(waiting)),
+        };
+        (sender, events)
+    }
+
+    /// A sender whose events nobody receives, for toasts shown without a handle.
+    pub fn discard() -> EventSender {
+        EventSender::channel().0
+    }
+
+    /// Send `event`, if the events are still received.
+    pub fn send(&self, event: ToastEvent) {
+        // Sending with the lock held, so `wait_async` either receives the event or is completed with it.
+        let mut waiting = lock(&self.waiting.0);
+        match waiting.completer.take() {
+            Some(completer) => completer.complete(Some(event)),
+            None => {
+                // A dropped receiver only means nobody cares about this toast any more.
+                let _ = self.sender.send(event);
+            }
+        }
+    }
+}
+
+/// The events of a shown toast, in the order they happened.
+///
+/// Iterating blocks until the next event, and ends once the backend drops its sender.
+#[derive(Debug)]
+pub struct ToastEvents {
+    receiver: Receiver<ToastEvent>,
+    dismissed: Cell<Option<DismissReason>>,
+    waiting: Arc<Mutex<Waiting>>,
+}
+
+impl ToastEvents {
+    /// The next event, waiting for it.
+    /// `None` once the backend will not send any more events.
+    pub fn recv(&self) -> Option<ToastEvent> {
+        self.received(self.receiver.recv().ok())
+    }
+
+    /// The next event, if there is one already.
+    pub fn try_recv(&self) -> Option<ToastEvent> {
+        self.received(self.receiver.try_recv().ok())
+    }
+
+    /// The next event if there is one already, `Err` once the backend dropped its sender and every event was received.
+    pub(crate) fn try_next(&self) -> std::result::Result<Option<ToastEvent>, TryRecvError> {
+        match self.receiver.try_recv() {
+            Ok(event) => Ok(self.received(Some(event))),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The next event, waiting at most `timeout` for it.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<ToastEvent> {
+        self.received(self.receiver.recv_timeout(timeout).ok())
+    }
+
+    /// Why the toast was dismissed, if a dismissal was received.
+    pub fn get_dismiss_reason(&self) -> Option<DismissReason> {
+        self.dismissed.get()
+    }
+
+    fn received(&self, event: Option<ToastEvent>) -> Option<ToastEvent> {
+        if let Some(ToastEvent::Dismissed { reason }) = event {
+            self.dismissed.set(Some(reason));
+        }
+        event
+    }
+
+    /// Wait at most `timeout` for the toast to be activated or dismissed.
+    ///
+    /// Returns the error of a [`ToastEvent::Failed`] if the toast could not be shown.
+    pub fn wait(&self, timeout: Duration) -> Result<ToastOutcome> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => self.outcome(event),
+            Err(RecvTimeoutError::Timeout) => Ok(ToastOutcome::Unanswered),
+            Err(RecvTimeoutError::Disconnected) => Ok(ToastOutcome::Closed),
+        }
+    }
+
+    fn outcome(&self, event: ToastEvent) -> Result<ToastOutcome> {
+        match event {
+            ToastEvent::Activated {
+                arguments,
+                user_inputs,
+            } => Ok(ToastOutcome::Activated {
+                arguments,
+                user_inputs,
+            }),
+            ToastEvent::Dismissed { reason } => {
+                self.dismissed.set(Some(reason));
+                Ok(ToastOutcome::Dismissed { reason })
+            }
+            ToastEvent::Failed { error } => Err(error),
+        }
+    }
+
+    /// [`ToastEvents::wait`] without blocking the async runtime.
+    ///
+    /// The backend completes the future when it sends the next event, and a timer thread shared by every wait once the timeout passed.
+    /// Dropping the future stops waiting.
+    pub fn wait_async(self, timeout: Duration) -> impl Future<Output = Result<ToastOutcome>> {
+        let (completer, next) = oneshot();
+        {
+            let mut waiting = lock(&self.waiting);
+            match self.receiver.try_recv() {
+                Ok(event) => completer.complete(Some(event)),
+                Err(TryRecvError::Disconnected) => drop(completer),
+                Err(TryRecvError::Empty) if timeout.is_zero() => completer.complete(None),
+                Err(TryRecvError::Empty) => {
+                    waiting.completer = Some(completer);
+                    let waiting = Arc::downgrade(&self.waiting);
+                    timer::after(timeout, move || {
+                        let completer = waiting
+                            .upgrade()
+                            .and_then(|waiting| lock(&waiting).completer.take());
+                        if let Some(completer) = completer {
+                            completer.complete(None);
+                        }
+                    });
+                }
+            }
+        }
+        WaitAsync { next, events: self }
+    }
+}
+
+/// The future of [`ToastEvents::wait_async`].
+struct WaitAsync {
+    next: Blocking<Option<ToastEvent>>,
+    events: ToastEvents,
+}
+
+impl Future for WaitAsync {
+    type Output = Result<ToastOutcome>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<ToastOutcome>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.next).poll(cx).map(|next| match next {
+            Ok(Some(event)) => this.events.outcome(event),
+            Ok(None) => Ok(ToastOutcome::Unanswered),
+            // The backend dropped its sender.
+            Err(Canceled) => Ok(ToastOutcome::Closed),
+        })
+    }
+}
+
+impl Drop for WaitAsync {
+    /// Stops waiting, later events are sent to the dropped events.
+    fn drop(&mut self) {
+        lock(&self.events.waiting).completer = None;
+    }
+}
+
+impl<'a> IntoIterator for &'a ToastEvents {
+    type Item = ToastEvent;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter { events: self }
+    }
+}
+
+/// Iterator over [`ToastEvents`], blocking until the next event.
+#[derive(Debug)]
+pub struct Iter<'a> {
+    events: &'a ToastEvents,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = ToastEvent;
+
+    fn next(&mut self) -> Option<ToastEvent> {
+        self.events.recv()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::future::{poll_fn, Future};
+    use std::sync::Arc;
+    use std::task::Poll;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::backend::MemoryBackend;
+    use crate::events::{lock, DismissReason, EventSender, ToastEvent, ToastOutcome};
+    use crate::notifier::Notifier;
+    use crate::Toast;
+
+    #[test]
+    fn waits_for_outcome() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+        let toast = Toast::new().unwrap();
+        let outcome = notifier.show_and_wait(&toast, Duration::ZERO);
+        assert_eq!(outcome.unwrap(), ToastOutcome::Unanswered);
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                while backend.toasts().len() < 2 {
+                    thread::yield_now();
+                }
+                let inputs = HashMap::from([("answer".to_string(), "yes".to_string())]);
+                backend.activate(1, "confirm", inputs);
+            });
+            let outcome = notifier.show_and_wait(&toast, Duration::from_secs(10));
+            let ToastOutcome::Activated {
+                arguments,
+                user_inputs,
+            } = outcome.unwrap()
+            else {
+                panic!("the toast was activated");
+            };
+            assert_eq!(arguments, "confirm");
+            assert_eq!(user_inputs["answer"], "yes");
+        });
+
+        let mut tagged = Toast::new().unwrap();
+        tagged.tag("question").unwrap();
+        let events = notifier.show(&tagged).unwrap().into_events();
+        notifier.show(&tagged).unwrap();
+        let outcome = events.wait(Duration::from_secs(10)).unwrap();
+        let replaced = ToastOutcome::Dismissed {
+            reason: DismissReason::Replaced,
+        };
+        assert_eq!(outcome, replaced);
+        assert_eq!(events.get_dismiss_reason(), Some(DismissReason::Replaced));
+        let outcome = events.wait(Duration::from_secs(10)).unwrap();
+        assert_eq!(outcome, ToastOutcome::Closed);
+    }
+
+    #[test]
+    fn events_end_with_the_toast() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+        let handle = notifier.show(&Toast::new().unwrap()).unwrap();
+        backend.dismiss(0, DismissReason::TimedOut);
+        backend.dismiss(0, DismissReason::UserCanceled);
+
+        // the handle still keeps the shown toast, but the backend dropped its sender
+        let reasons: Vec<_> = handle
+            .events()
+            .into_iter()
+            .map(|event| match event {
+                ToastEvent::Dismissed { reason } => reason,
+                event => panic!("the toast was only dismissed, not {event:?}"),
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            [DismissReason::TimedOut, DismissReason::UserCanceled]
+        );
+        assert_eq!(
+            handle.events().wait(Duration::from_secs(10)).unwrap(),
+            ToastOutcome::Closed
+        );
+        handle.hide().unwrap();
+    }
+
+    #[test]
+    fn maps_backend_reasons() {
+        let windows = [-1, 0, 1, 2, 3].map(DismissReason::from_windows);
+        let freedesktop = [0, 1, 2, 3, 4, 5].map(DismissReason::from_freedesktop);
+        assert_eq!(
+            windows,
+            [
+                DismissReason::Unknown,
+                DismissReason::UserCanceled,
+                DismissReason::ApplicationHidden,
+                DismissReason::TimedOut,
+                DismissReason::Unknown
+            ]
+        );
+        assert_eq!(
+            freedesktop,
+            [
+                DismissReason::Unknown,
+                DismissReason::TimedOut,
+                DismissReason::UserCanceled,
+                DismissReason::ApplicationHidden,
+                DismissReason::Unknown,
+                DismissReason::Unknown
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn waits_without_blocking() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+        let events = notifier.show(&Toast::new().unwrap()).unwrap().into_events();
+        let outcome = events.wait_async(Duration::from_secs(10));
+        backend.dismiss(0, DismissReason::UserCanceled);
+        assert_eq!(
+            outcome.await.unwrap(),
+            ToastOutcome::Dismissed {
+                reason: DismissReason::UserCanceled
+            }
+        );
+
+        // the timer answers when the backend does not
+        let (_sender, events) = EventSender::channel();
+        let outcome = events.wait_async(Duration::from_millis(10)).await;
+        assert_eq!(outcome.unwrap(), ToastOutcome::Unanswered);
+
+        // a dropped wait no longer takes the events of the sender
+        let (sender, events) = EventSender::channel();
+        let waiting = events.waiting.clone();
+        let mut dropped = Box::pin(events.wait_async(Duration::from_secs(10)));
+        let polled = poll_fn(|cx| Poll::Ready(dropped.as_mut().poll(cx))).await;
+        assert!(polled.is_pending());
+        drop(dropped);
+        assert!(lock(&waiting).completer.is_none());
+        sender.send(ToastEvent::Dismissed {
+            reason: DismissReason::UserCanceled,
+        });
+
+        // the wait ends once the backend drops its sender
+        let (sender, events) = EventSender::channel();
+        let outcome = events.wait_async(Duration::from_secs(10));
+        drop(sender);
+        assert_eq!(outcome.await.unwrap(), ToastOutcome::Closed);
+    }
+}
@@ -0,0 +1,143 @@
+//! The toasts of an app that are still in the Action Center.
+
+use std::sync::Arc;
+
+use crate::backend::{self, Backend, ToastPayload};
+use crate::error::Result;
+use crate::identity::ToastIdentity;
+
+/// The most toasts windows keeps in the Action Center for one app, older ones are dropped.
+pub const MAX_HISTORY: usize = 20;
+
+/// The Action Center history of one app, for listing and removing its toasts.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use windows_notifier::{backend::MemoryBackend, notifier::Notifier, Toast};
+/// let backend = Arc::new(MemoryBackend::new());
+/// let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+/// let mut toast = Toast::new()?;
+/// toast.tag("message-1")?;
+/// toast.group("thread-42")?;
+/// notifier.show(&toast)?;
+///
+/// // the user read the thread in the app
+/// let history = notifier.history();
+/// history.remove_group("thread-42")?;
+/// assert!(history.list()?.is_empty());
+/// # Ok::<(), windows_notifier::error::Error>(())
+/// ```
+pub struct ToastHistory {
+    backend: Arc<dyn Backend>,
+    app_id: String,
+}
+
+impl ToastHistory {
+    /// The history of `app_id` on this platform.
+    ///
+    /// Only supported on windows, other platforms return [`Error::Unsupported`](crate::error::Error::Unsupported) from every call.
+    pub fn new(app_id: &str) -> ToastHistory {
+        ToastHistory::with_backend(backend::platform(), app_id)
+    }
+
+    /// The history of `app_id` in `backend`, like a [`MemoryBackend`](backend::MemoryBackend) in tests.
+    pub fn with_backend(backend: Arc<dyn Backend>, app_id: &str) -> ToastHistory {
+        ToastHistory {
+            backend,
+            app_id: app_id.into(),
+        }
+    }
+
+    pub fn get_app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    /// The toasts in the Action Center, oldest first.
+    pub fn list(&self) -> Result<Vec<ToastPayload>> {
+        self.backend.history(&self.app_id)
+    }
+
+    /// Remove the toast with the tag and group of `identity`.
+    pub fn remove(&self, identity: &ToastIdentity) -> Result<()> {
+        identity.check_found("removed")?;
+        self.backend.remove(&self.app_id, identity)
+    }
+
+    /// Remove the toast with `tag` that has no group.
+    pub fn remove_tag(&self, tag: &str) -> Result<()> {
+        self.remove(&ToastIdentity::new(tag)?)
+    }
+
+    /// Remove every toast in `group`.
+    pub fn remove_group(&self, group: &str) -> Result<()> {
+        self.backend.remove_group(&self.app_id, group)
+    }
+
+    /// Remove every toast of the app.
+    pub fn clear(&self) -> Result<()> {
+        self.backend.clear(&self.app_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::backend::MemoryBackend;
+    use crate::events::{DismissReason, ToastEvent};
+    use crate::history::{ToastHistory, MAX_HISTORY};
+    use crate::identity::ToastIdentity;
+    use crate::notifier::Notifier;
+    use crate::Toast;
+
+    #[test]
+    fn removes_and_caps_history() {
+        let backend = Arc::new(MemoryBackend::new());
+        let show = |app_id: &str, tag: &str, group: Option<&str>| {
+            let mut toast = Toast::new().unwrap();
+            toast.tag(tag).unwrap();
+            if let Some(group) = group {
+                toast.group(group).unwrap();
+            }
+            Notifier::with_backend(backend.clone(), app_id)
+                .show(&toast)
+                .unwrap()
+        };
+        let handles: Vec<_> = (0..MAX_HISTORY + 2)
+            .map(|i| show("chat", &i.to_string(), Some("thread")))
+            .collect();
+        show("mail", "1", None);
+        for (i, handle) in handles.iter().enumerate() {
+            let reason = handle.events().try_recv().and_then(|event| match event {
+                ToastEvent::Dismissed { reason } => Some(reason),
+                _ => None,
+            });
+            let expected = (i < 2).then_some(DismissReason::HistoryFull);
+            assert_eq!(reason, expected, "toast {}", i);
+        }
+
+        let chat = ToastHistory::with_backend(backend.clone(), "chat");
+        let tags: Vec<_> = chat
+            .list()
+            .unwrap()
+            .iter()
+            .map(|toast| toast.identity.get_tag().unwrap().to_string())
+            .collect();
+        assert_eq!(tags.len(), MAX_HISTORY);
+        assert_eq!(tags[0], "2");
+
+        show("chat", "loose", None);
+        chat.remove(&ToastIdentity::new("2").unwrap().group("thread").unwrap())
+            .unwrap();
+        assert_eq!(chat.list().unwrap().len(), MAX_HISTORY);
+        chat.remove_group("thread").unwrap();
+        assert_eq!(chat.list().unwrap().len(), 1);
+        chat.remove_tag("loose").unwrap();
+        assert!(chat.list().unwrap().is_empty());
+
+        let mail = ToastHistory::with_backend(backend.clone(), "mail");
+        assert_eq!(mail.list().unwrap().len(), 1);
+        mail.clear().unwrap();
+        assert!(backend.toasts().is_empty());
+    }
+}
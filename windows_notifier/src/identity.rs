@@ -0,0 +1,229 @@
+//! Which shown toast is which, so a later toast can replace it and the app can update or remove it.
+//!
+//! A chat client can tag every toast with its thread, so a new message replaces the last one
+//! instead of adding another toast to the Action Center:
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use windows_notifier::{backend::MemoryBackend, identity::ToastIdentity, notifier::Notifier, Toast};
+//! let backend = Arc::new(MemoryBackend::new());
+//! let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+//! for message in ["Hi", "Are you there?"] {
+//!     let mut toast = Toast::new()?;
+//!     toast.title(message)?;
+//!     toast.identity(ToastIdentity::new("thread-42")?.group("chat")?)?;
+//!     notifier.show(&toast)?;
+//! }
+//! assert_eq!(backend.toasts().len(), 1);
+//! # Ok::<(), windows_notifier::error::Error>(())
+//! ```
+
+use std::sync::Arc;
+
+use crate::backend::{Backend, NotificationData, ShownToast, UpdateResult};
+use crate::error::{Error, Result};
+use crate::events::{DismissReason, EventSender, ToastEvents};
+use crate::Toast;
+
+/// The longest tag or group windows accepts.
+pub const MAX_TAG_LENGTH: usize = 64;
+
+/// The tag and group of a toast, and the id it has on other devices.
+///
+/// A shown toast is replaced by the next toast of the same app with the same tag and group.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
+pub struct ToastIdentity {
+    pub(crate) tag: Option<String>,
+    pub(crate) group: Option<String>,
+    pub(crate) remote_id: Option<String>,
+}
+
+impl ToastIdentity {
+    /// An identity with `tag`, at most [`MAX_TAG_LENGTH`] characters.
+    pub fn new(tag: &str) -> Result<ToastIdentity> {
+        Ok(ToastIdentity {
+            tag: Some(check_tag("tag", tag)?),
+            group: None,
+            remote_id: None,
+        })
+    }
+
+    /// The group of the tag, so the same tag can be used in different groups.
+    /// At most [`MAX_TAG_LENGTH`] characters.
+    pub fn group(mut self, group: &str) -> Result<Self> {
+        self.group = Some(check_tag("group", group)?);
+        Ok(self)
+    }
+
+    /// The id of the same notification on other devices, so dismissing it on one dismisses it everywhere.
+    pub fn remote_id(mut self, remote_id: &str) -> Self {
+        self.remote_id = Some(remote_id.into());
+        self
+    }
+
+    pub fn get_tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    pub fn get_group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    pub fn get_remote_id(&self) -> Option<&str> {
+        self.remote_id.as_deref()
+    }
+
+    /// Whether a toast with `other` replaces a shown toast with this identity.
+    pub fn replaces(&self, other: &ToastIdentity) -> bool {
+        self.tag.is_some() && self.tag == other.tag && self.group == other.group
+    }
+
+    /// Shown toasts can only be found again by their tag.
+    pub(crate) fn check_found(&self, operation: &str) -> Result<()> {
+        match self.tag {
+            Some(_) => Ok(()),
+            None => Err(Error::invalid_argument(
+                "tag",
+                format!("only toasts with a tag can be {}", operation),
+            )),
+        }
+    }
+}
+
+/// A shown toast, returned by [`Notifier::show`](crate::notifier::Notifier::show).
+///
+/// The handle only holds the identity, the events and what the backend kept of the shown toast,
+/// so it can be kept after the toast is dropped and sent to other threads.
+pub struct ToastHandle {
+    backend: Arc<dyn Backend>,
+    app_id: String,
+    identity: ToastIdentity,
+    events: ToastEvents,
+    shown: ShownToast,
+}
+
+impl ToastHandle {
+    pub(crate) fn new(
+        backend: Arc<dyn Backend>,
+        app_id: &str,
+        identity: &ToastIdentity,
+        events: ToastEvents,
+        shown: ShownToast,
+    ) -> Self {
+        ToastHandle {
+            backend,
+            app_id: app_id.into(),
+            identity: identity.clone(),
+            events,
+            shown,
+        }
+    }
+
+    /// Activations, dismissals and failures of the toast.
+    pub fn events(&self) -> &ToastEvents {
+        &self.events
+    }
+
+    /// Why the toast was dismissed, once the dismissal was received from the [events](ToastHandle::events).
+    pub fn get_dismiss_reason(&self) -> Option<DismissReason> {
+        self.events.get_dismiss_reason()
+    }
+
+    /// The events, without the handle.
+    pub fn into_events(self) -> ToastEvents {
+        self.events
+    }
+
+    pub fn get_app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    pub fn get_identity(&self) -> &ToastIdentity {
+        &self.identity
+    }
+
+    /// Change the `{key}` bindings of the shown toast, found by its app id, tag and group.
+    ///
+    /// Only supported on windows, other platforms return [`Error::Unsupported`].
+    pub fn update(&self, data: &NotificationData) -> Result<UpdateResult> {
+        self.identity.check_found("updated")?;
+        self.backend.update(&self.app_id, &self.identity, data)
+    }
+
+    /// Take the toast off the screen if it is still shown, like when the call it announces was answered elsewhere.
+    /// A toast that already moved to the Action Center stays there, use [`ToastHandle::remove`] for it.
+    ///
+    /// The toast is found by the handle, so toasts without a tag can be hidden too.
+    pub fn hide(&self) -> Result<()> {
+        if self.shown.is_empty() {
+            self.identity.check_found("hidden")?;
+        }
+        self.backend.hide(&self.app_id, &self.identity, &self.shown)
+    }
+
+    /// Remove the toast from the screen and the Action Center.
+    /// Removing a toast that is already gone is not an error.
+    pub fn remove(&self) -> Result<()> {
+        self.identity.check_found("removed")?;
+        self.backend.remove(&self.app_id, &self.identity)
+    }
+
+    /// Show the content of `toast` in place of the toast, with the app id and identity of the handle.
+    /// The events are the events of `toast` from now on.
+    pub fn replace<S, C>(&mut self, toast: &Toast<S, C>) -> Result<()> {
+        self.identity.check_found("replaced")?;
+        let mut payload = toast.payload()?;
+        payload.identity = self.identity.clone();
+        let (sender, events) = EventSender::channel();
+        self.shown = self.backend.show(&self.app_id, &payload, sender)?;
+        self.events = events;
+        Ok(())
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// Set the tag, group and remote id of the toast.
+    pub fn identity(&mut self, identity: ToastIdentity) -> Result<()> {
+        self.identity = identity;
+        Ok(())
+    }
+
+    /// The tag of the toast, used to replace, [update](ToastHandle::update) or remove it once it is shown.
+    /// At most [`MAX_TAG_LENGTH`] characters.
+    pub fn tag(&mut self, tag: &str) -> Result<()> {
+        self.identity.tag = Some(check_tag("tag", tag)?);
+        Ok(())
+    }
+
+    /// The group of the tag, so the same tag can be used in different groups.
+    /// At most [`MAX_TAG_LENGTH`] characters.
+    pub fn group(&mut self, group: &str) -> Result<()> {
+        self.identity.group = Some(check_tag("group", group)?);
+        Ok(())
+    }
+
+    /// The id of the same notification on other devices.
+    pub fn remote_id(&mut self, remote_id: &str) -> Result<()> {
+        self.identity.remote_id = Some(remote_id.into());
+        Ok(())
+    }
+
+    pub fn get_identity(&self) -> &ToastIdentity {
+        &self.identity
+    }
+}
+
+fn check_tag(path: &str, tag: &str) -> Result<String> {
+    if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+        return Err(Error::invalid_argument(
+            path,
+            format!("must be 1 to {} characters", MAX_TAG_LENGTH),
+        ));
+    }
+    Ok(tag.into())
+}
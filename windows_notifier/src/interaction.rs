@@ -0,0 +1,150 @@
+//! Multi-step interactions in a single toast, like confirming a reply and then showing that it was sent.
+//!
+//! A button with [`AfterActivationBehavior::PendingUpdate`] keeps the toast on screen after it is clicked,
+//! showing that it is busy, until the app shows the next step in its place.
+//! Every step is shown with the identity of the first toast, so it replaces the toast instead of adding one.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use windows_notifier::{backend::MemoryBackend, interaction::Interaction, notifier::Notifier, Toast};
+//! # use windows_notifier::events::ToastEvent;
+//! # use windows_notifier::tags::{action::Action, activation::{ActivationType, AfterActivationBehavior}};
+//! let backend = Arc::new(MemoryBackend::new());
+//! let notifier = Notifier::with_backend(backend.clone(), "Contoso.Reports");
+//! let mut confirm = Toast::new()?;
+//! confirm.title("Send the report?")?;
+//! confirm.tag("report")?;
+//! confirm.add_action(
+//!     Action::new("Send".into(), "send")
+//!         .activation_type(ActivationType::Background)
+//!         .after_activation_behavior(AfterActivationBehavior::PendingUpdate),
+//! )?;
+//! let mut interaction = Interaction::start(&notifier, &confirm)?;
+//!
+//! backend.activate(0, "send", Default::default());
+//! if let Some(ToastEvent::Activated { .. }) = interaction.events().try_recv() {
+//!     let mut sending = Toast::new()?;
+//!     sending.title("Sending...")?;
+//!     interaction.step(&sending)?;
+//!
+//!     let mut sent = Toast::new()?;
+//!     sent.title("Sent")?;
+//!     interaction.step(&sent)?;
+//! }
+//! assert_eq!(interaction.get_step(), 2);
+//! assert_eq!(backend.toasts().len(), 1);
+//! # Ok::<(), windows_notifier::error::Error>(())
+//! ```
+//!
+//! [`AfterActivationBehavior::PendingUpdate`]: crate::tags::activation::AfterActivationBehavior::PendingUpdate
+
+use crate::error::Result;
+use crate::events::ToastEvents;
+use crate::identity::ToastHandle;
+use crate::notifier::Notifier;
+use crate::Toast;
+
+/// A toast whose content is replaced in place, step by step.
+pub struct Interaction {
+    handle: ToastHandle,
+    step: usize,
+}
+
+impl Interaction {
+    /// Show the first step, `toast`, with `notifier`.
+    ///
+    /// Returns [`Error::InvalidArgument`](crate::error::Error::InvalidArgument) if `toast` has no tag,
+    /// which the later steps need to replace it.
+    pub fn start<S, C>(notifier: &Notifier, toast: &Toast<S, C>) -> Result<Interaction> {
+        toast.get_identity().check_found("replaced in place")?;
+        Ok(Interaction {
+            handle: notifier.show(toast)?,
+            step: 0,
+        })
+    }
+
+    /// Show the content of `toast` as the next step, in place of the current one.
+    pub fn step<S, C>(&mut self, toast: &Toast<S, C>) -> Result<()> {
+        self.handle.replace(toast)?;
+        self.step += 1;
+        Ok(())
+    }
+
+    /// The number of steps shown after the first one.
+    pub fn get_step(&self) -> usize {
+        self.step
+    }
+
+    /// The events of the current step.
+    pub fn events(&self) -> &ToastEvents {
+        self.handle.events()
+    }
+
+    /// The handle of the current step.
+    pub fn handle(&self) -> &ToastHandle {
+        &self.handle
+    }
+
+    /// End the interaction, keeping the handle of the last step.
+    pub fn finish(self) -> ToastHandle {
+        self.handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use crate::backend::MemoryBackend;
+    use crate::events::{DismissReason, ToastEvent};
+    use crate::interaction::Interaction;
+    use crate::notifier::Notifier;
+    use crate::tags::action::Action;
+    use crate::tags::activation::{ActivationType, AfterActivationBehavior};
+    use crate::Toast;
+
+    #[test]
+    fn replaces_pending_toast() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Reports");
+        let mut confirm = Toast::new().unwrap();
+        confirm.title("Delete 3 files?").unwrap();
+        confirm
+            .add_action(
+                Action::new("Delete".into(), "delete")
+                    .activation_type(ActivationType::Background)
+                    .after_activation_behavior(AfterActivationBehavior::PendingUpdate),
+            )
+            .unwrap();
+        confirm
+            .add_action(Action::new("Keep".into(), "keep"))
+            .unwrap();
+        assert!(Interaction::start(&notifier, &confirm).is_err());
+
+        confirm.tag("delete").unwrap();
+        let mut interaction = Interaction::start(&notifier, &confirm).unwrap();
+        assert!(backend.activate(0, "delete", HashMap::new()));
+        assert!(matches!(
+            interaction.events().try_recv(),
+            Some(ToastEvent::Activated { .. })
+        ));
+        assert_eq!(backend.toasts().len(), 1);
+
+        let mut deleted = Toast::new().unwrap();
+        deleted.title("Deleted").unwrap();
+        interaction.step(&deleted).unwrap();
+        let toasts = backend.toasts();
+        assert_eq!(toasts.len(), 1);
+        assert!(toasts[0].payload.xml.contains("Deleted"));
+        assert_eq!(toasts[0].payload.identity.get_tag(), Some("delete"));
+
+        let handle = interaction.finish();
+        assert!(backend.dismiss(0, DismissReason::UserCanceled));
+        assert!(handle.events().try_recv().is_some());
+
+        Interaction::start(&notifier, &confirm).unwrap();
+        assert!(backend.activate(0, "keep", HashMap::new()));
+        assert!(backend.toasts().is_empty());
+    }
+}
@@ -1,76 +1,418 @@
+use std::io::BufRead;
 use std::marker::PhantomData;
+use std::time::SystemTime;
 
-use crate::error::Result;
-use utils::into_raw::ToXML;
-use windows::{
-    core::HSTRING,
-    Data::Xml::Dom::XmlDocument,
-    UI::Notifications::{ToastNotification, ToastNotificationManager},
-};
+use crate::backend::{NotificationData, ToastPayload};
+use crate::error::{Result, XmlErr};
+use crate::identity::ToastIdentity;
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
+use raw::{Attributes, Extra, FromXML, RawElement};
+use tags::actions::Actions;
+use tags::activation::ActivationType;
+use tags::audio::Audio;
+use tags::commands::Command;
+use tags::header::Header;
+use tags::toast::{Duration, Scenarios};
+use tags::visual::Visual;
+use utils::into_raw::{write_children, ToXML};
+use utils::time::{format_iso8601, parse_iso8601};
 
+pub mod arguments;
+pub mod backend;
+#[cfg(feature = "serde")]
+pub mod definition;
 pub mod error;
+pub mod events;
+pub mod history;
+pub mod identity;
+pub mod interaction;
+pub mod localize;
+pub mod notifier;
+pub mod queue;
 // pub mod new;
+pub mod raw;
+pub mod router;
+pub mod schedule;
+pub mod snooze;
 pub mod tags;
+pub mod template;
 mod utils;
+pub mod validate;
+#[cfg(windows)]
 mod windows_check;
 
 pub struct HasAudio;
 
-pub struct Toast<S = ()> {
-    doc: XmlDocument,
-    app_id: HSTRING,
-    phantom: PhantomData<S>,
+/// A toast notification.
+///
+/// The toast is kept as a plain rust value and is only turned into a WinRT `XmlDocument` when it is shown
+/// by a [`Notifier`](notifier::Notifier), so it can be built and inspected on any platform.
+///
+/// `S` tracks whether audio has been added, `C` the scenario set with [`Toast::scenario`].
+pub struct Toast<S = (), C = ()> {
+    duration: Option<Duration>,
+    launch: Option<String>,
+    activation_type: Option<ActivationType>,
+    target_application_pfn: Option<String>,
+    scenario: Option<Scenarios>,
+    use_button_style: bool,
+    display_timestamp: Option<SystemTime>,
+    visual: Visual,
+    actions: Actions,
+    audio: Option<Audio>,
+    commands: Vec<Command>,
+    header: Option<Header>,
+    extra: Extra,
+    identity: ToastIdentity,
+    data: Option<NotificationData>,
+    expiration: Option<SystemTime>,
+    expires_on_reboot: bool,
+    phantom: PhantomData<(S, C)>,
 }
 
 impl Toast<()> {
-    /// This can be used if you do not have a AppUserModelID.
-    ///
-    /// However, the toast will erroniously report its origin as powershell.
-    pub const POWERSHELL_APP_ID: &'static str = "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\
-                                                 \\WindowsPowerShell\\v1.0\\powershell.exe";
-
     pub fn new() -> Result<Toast<()>> {
-        let doc = XmlDocument::new()?;
-
-        doc.LoadXml(&HSTRING::from(
-            "<toast> 
-                <visual id=\"visual\">
-                    <binding template=\"ToastGeneric\">
-                        <text id=\"0\"></text>
-                    </binding>
-                </visual>
-                <actions>
-                </actions>
-            </toast>",
-        ))?;
-
         Ok(Toast {
-            doc,
-            app_id: Toast::POWERSHELL_APP_ID.into(),
+            duration: None,
+            launch: None,
+            activation_type: None,
+            target_application_pfn: None,
+            scenario: None,
+            use_button_style: false,
+            display_timestamp: None,
+            visual: Visual::new(),
+            actions: Actions::new(),
+            audio: None,
+            commands: Vec::new(),
+            header: None,
+            extra: Extra::default(),
+            identity: ToastIdentity::default(),
+            data: None,
+            expiration: None,
+            expires_on_reboot: false,
             phantom: PhantomData,
         })
     }
+
+    /// Parse a toast from its xml, like the output of [`Toast::into_raw`] or a payload from the Microsoft docs.
+    ///
+    /// Elements and attributes that have no typed representation are kept as they are and written back out by [`Toast::into_raw`].
+    /// Any `audio` element is read into the toast, so [`Toast::add_audio`] replaces it instead of adding a second one.
+    pub fn from_xml(xml: &str) -> Result<Toast<()>> {
+        Toast::from_reader(xml.as_bytes())
+    }
+
+    /// Parse a toast from a reader, see [`Toast::from_xml`].
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Toast<()>> {
+        Toast::from_element(&RawElement::from_reader(reader)?)
+    }
+
+    /// Reads a toast from its root element, see [`Toast::from_xml`].
+    pub(crate) fn from_element(element: &RawElement) -> Result<Toast<()>> {
+        if element.name != "toast" {
+            return Err(
+                XmlErr::Parse(format!("expected a toast element, found {}", element.name)).into(),
+            );
+        }
+
+        let mut toast = Toast::new()?;
+        let mut attributes = Attributes::new(element);
+        toast.duration = attributes.take_with("duration", Duration::from_attr);
+        toast.launch = attributes.take("launch");
+        toast.activation_type = attributes.take_with("activationType", ActivationType::from_attr);
+        toast.target_application_pfn = attributes.take("protocolActivationTargetApplicationPfn");
+        toast.scenario = attributes.take_with("scenario", Scenarios::from_attr);
+        toast.use_button_style = attributes.take_bool("useButtonStyle");
+        toast.display_timestamp = attributes.take_with("displayTimestamp", parse_iso8601);
+
+        let mut visual = None;
+        let mut actions = None;
+        let mut unknown = Vec::new();
+        for child in element.elements() {
+            let known = match child.name.as_str() {
+                "visual" if visual.is_none() => {
+                    visual = Visual::from_raw(child);
+                    visual.is_some()
+                }
+                "actions" if actions.is_none() => {
+                    actions = Actions::from_raw(child);
+                    actions.is_some()
+                }
+                "audio" if toast.audio.is_none() => {
+                    toast.audio = Audio::from_raw(child);
+                    toast.audio.is_some()
+                }
+                "commands" if toast.commands.is_empty() => {
+                    toast.commands = Command::read_commands(child).unwrap_or_default();
+                    !toast.commands.is_empty()
+                }
+                "header" if toast.header.is_none() => {
+                    toast.header = Header::from_raw(child);
+                    toast.header.is_some()
+                }
+                _ => false,
+            };
+            if !known {
+                unknown.push(child.clone());
+            }
+        }
+
+        toast.visual = visual.ok_or_else(|| XmlErr::Parse("toast has no visual binding".into()))?;
+        toast.actions = actions.unwrap_or_default();
+        toast.extra = attributes.into_extra(unknown);
+        Ok(toast)
+    }
 }
 
-impl<S> Toast<S> {
-    /// Set the AppUserModelID for the toast.
-    pub fn app_id(&mut self, app_id: &str) -> Result<()> {
-        self.app_id = app_id.into();
+impl<S, C> Toast<S, C> {
+    /// The values of the `{key}` bindings when the toast is shown.
+    pub fn data(&mut self, data: NotificationData) -> Result<()> {
+        self.data = Some(data);
         Ok(())
     }
 
-    pub fn show(&self) -> Result<()> {
-        println!("{:?}", self.into_raw()?);
-        let notifier = ToastNotificationManager::CreateToastNotifierWithId(&self.app_id)?;
-        let toast = ToastNotification::CreateToastNotification(&self.doc)?;
-        let res = notifier.Show(&toast);
+    pub fn get_data(&self) -> Option<&NotificationData> {
+        self.data.as_ref()
+    }
+
+    /// What a backend gets to show the toast.
+    pub(crate) fn payload(&self) -> Result<ToastPayload> {
+        Ok(ToastPayload {
+            xml: self.into_raw()?,
+            identity: self.identity.clone(),
+            data: self.data.clone(),
+            expiration: self.expiration,
+            expires_on_reboot: self.expires_on_reboot,
+        })
+    }
+
+    /// The toast as the xml that is handed to windows.
+    pub fn into_raw(&self) -> Result<String> {
+        let mut writer = Writer::new(Vec::new());
+        self.write_toast(&mut writer)?;
+        String::from_utf8(writer.into_inner())
+            .map_err(|err| error::XmlErr::Serialize(err.to_string()).into())
+    }
+
+    fn write_toast<W: std::io::Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut toast = BytesStart::new("toast");
+        if let Some(duration) = self.duration {
+            toast.push_attribute(("duration", duration.to_string().as_str()));
+        }
+        if let Some(launch) = &self.launch {
+            toast.push_attribute(("launch", launch.as_str()));
+        }
+        if let Some(activation_type) = self.activation_type {
+            toast.push_attribute(("activationType", activation_type.to_string().as_str()));
+        }
+        if let Some(pfn) = &self.target_application_pfn {
+            toast.push_attribute(("protocolActivationTargetApplicationPfn", pfn.as_str()));
+        }
+        if let Some(scenario) = self.scenario {
+            toast.push_attribute(("scenario", scenario.to_string().as_str()));
+        }
+        if self.use_button_style {
+            toast.push_attribute(("useButtonStyle", "true"));
+        }
+        if let Some(display_timestamp) = self.display_timestamp {
+            toast.push_attribute((
+                "displayTimestamp",
+                format_iso8601(display_timestamp).as_str(),
+            ));
+        }
+        self.extra.push_attributes(&mut toast);
+
+        write_children(writer, toast, |writer| {
+            self.visual.write_xml(writer)?;
+            if !self.actions.is_empty() {
+                self.actions.write_xml(writer)?;
+            }
+            if let Some(audio) = &self.audio {
+                audio.write_xml(writer)?;
+            }
+            if !self.commands.is_empty() {
+                Command::write_commands(&self.commands, writer)?;
+            }
+            if let Some(header) = &self.header {
+                header.write_xml(writer)?;
+            }
+            self.extra.write_elements(writer)
+        })
+    }
+
+    /// Moves the toast content into a toast of another state.
+    fn into_state<T, D>(self) -> Toast<T, D> {
+        Toast {
+            duration: self.duration,
+            launch: self.launch,
+            activation_type: self.activation_type,
+            target_application_pfn: self.target_application_pfn,
+            scenario: self.scenario,
+            use_button_style: self.use_button_style,
+            display_timestamp: self.display_timestamp,
+            visual: self.visual,
+            actions: self.actions,
+            audio: self.audio,
+            commands: self.commands,
+            header: self.header,
+            extra: self.extra,
+            identity: self.identity,
+            data: self.data,
+            expiration: self.expiration,
+            expires_on_reboot: self.expires_on_reboot,
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::action::Action;
+    use crate::tags::activation::ActivationType;
+    use crate::tags::audio::{Audio, Notification};
+    use crate::tags::commands::AlarmCommands;
+    use crate::tags::header::Header;
+    use crate::tags::image::Image;
+    use crate::tags::input::{Input, Selection};
+    use crate::tags::progress::{Progress, Value};
+    use crate::tags::sub_group::Child;
+    use crate::tags::text::Text;
+    use crate::tags::toast::{Alarm, Duration};
+    use crate::*;
+
+    #[test]
+    fn round_trip() {
+        let mut toast = Toast::new().unwrap();
+        toast.title("Andrew sent you a picture").unwrap();
+        toast
+            .add_text(Text::new("Check this out & reply").id(1))
+            .unwrap();
+        toast
+            .add_input(Input::new_selection(
+                "snoozeTime",
+                vec![
+                    Selection::new("5", "5 minutes"),
+                    Selection::new("15", "15 minutes"),
+                ],
+                Some("15"),
+            ))
+            .unwrap();
+        toast
+            .add_action(
+                Action::new("Reply".into(), "action=reply&threadId=92187")
+                    .activation_type(ActivationType::Background),
+            )
+            .unwrap();
+        let raw = toast.into_raw().unwrap();
+
+        let parsed = Toast::from_xml(&raw).unwrap();
+        assert_eq!(parsed.into_raw().unwrap(), raw);
+        assert_eq!(
+            parsed.actions()[0].get_arguments(),
+            "action=reply&threadId=92187"
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_content() {
+        let xml = r#"<toast launch="app-defined-string" displayTimestamp="2017-04-15T19:45:00Z">
+            <visual>
+                <binding template="ToastGeneric">
+                    <text hint-maxLines="1">Adaptive Tiles Meeting</text>
+                    <text>Conf Room 2001 / Building 135</text>
+                    <image src="https://picsum.photos/48?image=883" hint-removeMargin="true"/>
+                    <progress value="{progressValue}" status="{progressStatus}"/>
+                </binding>
+            </visual>
+            <actions>
+                <action activationType="system" arguments="dismiss" content=""/>
+            </actions>
+        </toast>"#;
+        let toast = Toast::from_xml(xml).unwrap();
+        assert_eq!(toast.get_launch(), Some("app-defined-string"));
+        assert!(toast.get_display_timestamp().is_some());
+        assert_eq!(toast.binding_children().len(), 4);
+        assert_eq!(
+            toast.actions()[0].get_system_action(),
+            Some(tags::activation::SystemAction::Dismiss)
+        );
+
+        let raw = toast.into_raw().unwrap();
+        assert!(raw.contains(r#"displayTimestamp="2017-04-15T19:45:00Z""#));
+        assert!(raw.contains(r#"hint-maxLines="1""#));
+        assert!(raw.contains(r#"value="{progressValue}""#));
+        assert!(raw.contains(r#"activationType="system""#));
+        assert_eq!(Toast::from_xml(&raw).unwrap().into_raw().unwrap(), raw);
+    }
 
-        std::thread::sleep(std::time::Duration::from_millis(10));
+    #[test]
+    fn builds_after_audio() {
+        let mut toast = Toast::new()
+            .unwrap()
+            .scenario(Alarm)
+            .unwrap()
+            .add_audio(Audio::new(Notification::LoopingAlarm).loop_())
+            .unwrap();
+        toast.duration(Duration::Short).unwrap();
+        toast.launch("action=open").unwrap();
+        toast.styled_button(true).unwrap();
+        toast.visual_add_image_query().unwrap();
+        toast.visual_base_uri("https://example.com/").unwrap();
+        toast.binding_add_image_query().unwrap();
+        toast.binding_base_uri("https://example.com/").unwrap();
+        toast.binding_fallback("fallback").unwrap();
+        toast.title("Wake up").unwrap();
+        toast.add_text(Text::new("It is 7:00")).unwrap();
+        toast.add_image(Image::new("sun.png")).unwrap();
+        toast
+            .add_progress(Progress::new("Sleeping", Value::Indeterminate))
+            .unwrap();
+        toast
+            .add_sub_group(vec![Child::Text(Text::new("Monday"))])
+            .unwrap();
+        toast
+            .add_header(Header::new("alarms", "Alarms", "action=alarms"))
+            .unwrap();
+        toast
+            .add_input(Input::new_text("note", Some("note")))
+            .unwrap();
+        toast
+            .add_action(Action::new("Open".into(), "action=open"))
+            .unwrap();
+        toast
+            .add_command(AlarmCommands::Snooze, "action=snooze")
+            .unwrap();
 
-        Ok(res?)
+        let raw = toast.into_raw().unwrap();
+        assert!(raw.starts_with(
+            r#"<toast duration="short" launch="action=open" scenario="alarm" useButtonStyle="true">"#
+        ));
+        for element in [
+            "<audio",
+            "<progress",
+            "<subgroup",
+            "<header",
+            "<input",
+            "<action ",
+            "<commands",
+        ] {
+            assert!(raw.contains(element), "{} missing from {}", element, raw);
+        }
+        assert!(toast.validate().is_empty());
     }
 
-    pub fn into_raw(&self) -> Result<HSTRING> {
-        Ok(self.doc.GetXml()?)
+    #[test]
+    fn scenario_after_audio() {
+        let mut toast = Toast::new()
+            .unwrap()
+            .add_audio(Audio::new(Notification::Default))
+            .unwrap()
+            .scenario(Alarm)
+            .unwrap();
+        toast
+            .add_command(AlarmCommands::Dismiss, "action=dismiss")
+            .unwrap();
+        assert_eq!(toast.get_scenario(), Some(tags::toast::Scenarios::Alarm));
     }
 }
@@ -0,0 +1,450 @@
+//! Localized toast content, where text is keyed to message ids that are resolved for a locale.
+//!
+//! Content is marked with a message id, like [`Text::message`](crate::tags::text::Text::message),
+//! and [`Toast::localize`] replaces it with the message from the first locale in the fallback chain that has it.
+
+#[cfg(feature = "fluent")]
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::tags::binding::BindingChild;
+use crate::tags::image::Image;
+use crate::tags::input::Input;
+use crate::tags::sub_group::Child;
+use crate::tags::text::Text;
+use crate::Toast;
+
+/// Where messages are looked up, like [`MessageTables`] or, with the `fluent` feature, [`FluentMessages`].
+pub trait Messages {
+    /// The message `id` in `locale`, if that locale has it.
+    fn message(&self, locale: &str, id: &str) -> Option<String>;
+}
+
+impl<M: Messages + ?Sized> Messages for &M {
+    fn message(&self, locale: &str, id: &str) -> Option<String> {
+        (**self).message(locale, id)
+    }
+}
+
+/// A plain table of messages per locale.
+///
+/// With the `serde` feature the tables can be loaded from a map of locales to maps of message ids,
+/// like `{ "de": { "greeting": "Hallo" } }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct MessageTables {
+    tables: HashMap<String, HashMap<String, String>>,
+}
+
+impl MessageTables {
+    pub fn new() -> MessageTables {
+        MessageTables::default()
+    }
+
+    /// Add message `id` to `locale`, replacing it if it was already there.
+    pub fn insert(&mut self, locale: &str, id: &str, message: &str) {
+        self.tables
+            .entry(locale.into())
+            .or_default()
+            .insert(id.into(), message.into());
+    }
+
+    /// Add all `messages` to `locale`, as message id and message pairs.
+    pub fn add_table<K, V>(&mut self, locale: &str, messages: impl IntoIterator<Item = (K, V)>)
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let table = self.tables.entry(locale.into()).or_default();
+        table.extend(
+            messages
+                .into_iter()
+                .map(|(id, message)| (id.into(), message.into())),
+        );
+    }
+}
+
+impl Messages for MessageTables {
+    fn message(&self, locale: &str, id: &str) -> Option<String> {
+        self.tables.get(locale)?.get(id).cloned()
+    }
+}
+
+/// Messages from fluent resources, one bundle per locale.
+#[cfg(feature = "fluent")]
+#[derive(Default)]
+pub struct FluentMessages {
+    bundles: HashMap<String, fluent_bundle::FluentBundle<fluent_bundle::FluentResource>>,
+}
+
+#[cfg(feature = "fluent")]
+impl FluentMessages {
+    pub fn new() -> FluentMessages {
+        FluentMessages::default()
+    }
+
+    /// Parse `source` as fluent and add its messages to `locale`.
+    pub fn add_resource(&mut self, locale: &str, source: &str) -> Result<()> {
+        let resource =
+            fluent_bundle::FluentResource::try_new(source.into()).map_err(|(_, errors)| {
+                Error::invalid_argument(locale, format!("invalid fluent: {:?}", errors))
+            })?;
+        let bundle = match self.bundles.entry(locale.into()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let langid: unic_langid::LanguageIdentifier = locale
+                    .parse()
+                    .map_err(|_| Error::invalid_argument(locale, "not a language identifier"))?;
+                let mut bundle = fluent_bundle::FluentBundle::new(vec![langid]);
+                // Toasts are plain text, the unicode isolation marks would show up as boxes.
+                bundle.set_use_isolating(false);
+                entry.insert(bundle)
+            }
+        };
+        bundle.add_resource(resource).map_err(|errors| {
+            Error::invalid_argument(locale, format!("invalid fluent: {:?}", errors))
+        })
+    }
+}
+
+#[cfg(feature = "fluent")]
+impl Messages for FluentMessages {
+    fn message(&self, locale: &str, id: &str) -> Option<String> {
+        let bundle = self.bundles.get(locale)?;
+        let pattern = bundle.get_message(id)?.value()?;
+        let mut errors = Vec::new();
+        let message = bundle.format_pattern(pattern, None, &mut errors);
+        errors.is_empty().then(|| message.into_owned())
+    }
+}
+
+/// Resolves message ids with a fallback chain of locales, like `de-AT` → `de` → `en`.
+pub struct Localizer<M> {
+    messages: M,
+    locales: Vec<String>,
+}
+
+impl<M: Messages> Localizer<M> {
+    /// Look messages up in `locale`, then in its parents, so `de-AT` falls back to `de`.
+    pub fn new(messages: M, locale: &str) -> Localizer<M> {
+        Localizer {
+            messages,
+            locales: Vec::new(),
+        }
+        .fallback(locale)
+    }
+
+    /// Add `locale` and its parents to the end of the fallback chain.
+    pub fn fallback(mut self, locale: &str) -> Self {
+        let mut locale = locale;
+        loop {
+            if !self.locales.iter().any(|l| l == locale) {
+                self.locales.push(locale.into());
+            }
+            match locale.rfind('-') {
+                Some(index) => locale = &locale[..index],
+                None => return self,
+            }
+        }
+    }
+
+    /// The fallback chain, the first locale is the one the toast is for.
+    pub fn locales(&self) -> &[String] {
+        &self.locales
+    }
+
+    /// Message `id` from the first locale in the chain that has it.
+    pub fn message(&self, id: &str) -> Option<String> {
+        self.resolve(id).map(|(_, message)| message)
+    }
+
+    /// Message `id` and the locale it was found in.
+    pub fn resolve(&self, id: &str) -> Option<(&str, String)> {
+        self.locales.iter().find_map(|locale| {
+            self.messages
+                .message(locale, id)
+                .map(|message| (locale.as_str(), message))
+        })
+    }
+}
+
+/// Content that is replaced by a message.
+enum Slot<'a> {
+    Text(&'a mut String),
+    Optional(&'a mut Option<String>),
+}
+
+impl<S, C> Toast<S, C> {
+    /// Replace all content keyed to a message id with the message from `localizer`.
+    ///
+    /// The `lang` of the visual is set to the locale the messages were found in,
+    /// or unset if they come from different locales, like when some fell back to `en`.
+    /// A toast without message ids keeps its `lang`.
+    ///
+    /// Returns [`Error::MissingMessages`] without changing the toast if a message is in none of the locales.
+    pub fn localize<M: Messages>(&mut self, localizer: &Localizer<M>) -> Result<()> {
+        let mut messages = HashMap::new();
+        let mut locales = Vec::new();
+        let mut missing = Vec::new();
+        self.visit_messages(&mut |id, _| {
+            if messages.contains_key(id) || missing.iter().any(|m| m == id) {
+                return;
+            }
+            match localizer.resolve(id) {
+                Some((locale, message)) => {
+                    if !locales.contains(&locale) {
+                        locales.push(locale);
+                    }
+                    messages.insert(id.to_string(), message);
+                }
+                None => missing.push(id.to_string()),
+            }
+        });
+        if !missing.is_empty() {
+            return Err(Error::MissingMessages {
+                locales: localizer.locales().to_vec(),
+                ids: missing,
+            });
+        }
+
+        self.visit_messages(&mut |id, slot| {
+            let message = messages[id].clone();
+            match slot {
+                Slot::Text(text) => *text = message,
+                Slot::Optional(text) => *text = Some(message),
+            }
+        });
+        match locales[..] {
+            [] => {}
+            [locale] => self.visual.lang = Some(locale.into()),
+            _ => self.visual.lang = None,
+        }
+        Ok(())
+    }
+
+    fn visit_messages(&mut self, f: &mut dyn FnMut(&str, Slot)) {
+        for child in &mut self.visual.binding.children {
+            match child {
+                BindingChild::Text(text) => visit_text(text, f),
+                BindingChild::Image(image) => visit_image(image, f),
+                BindingChild::Group(group) => {
+                    for child in group.sub_groups.iter_mut().flat_map(|s| &mut s.children) {
+                        match child {
+                            Child::Text(text) => visit_text(text, f),
+                            Child::Image(image) => visit_image(image, f),
+                        }
+                    }
+                }
+                BindingChild::Progress(_) => {}
+            }
+        }
+        for action in &mut self.actions.actions {
+            if let Some(id) = &action.content_message {
+                f(id, Slot::Text(&mut action.content));
+            }
+        }
+        for input in &mut self.actions.inputs {
+            let (title, title_message) = match input {
+                Input::Text {
+                    title,
+                    title_message,
+                    place_holder_content,
+                    place_holder_message,
+                    ..
+                } => {
+                    if let Some(id) = place_holder_message {
+                        f(id, Slot::Optional(place_holder_content));
+                    }
+                    (title, title_message)
+                }
+                Input::Selection {
+                    title,
+                    title_message,
+                    ..
+                } => (title, title_message),
+            };
+            if let Some(id) = title_message {
+                f(id, Slot::Optional(title));
+            }
+        }
+    }
+    /// The message ids of all content that can have one, in document order, set or not.
+    pub(crate) fn message_ids(&self) -> Vec<Option<String>> {
+        let mut ids = Vec::new();
+        for child in &self.visual.binding.children {
+            match child {
+                BindingChild::Text(text) => ids.push(text.message.clone()),
+                BindingChild::Image(image) => ids.push(image.alt_message.clone()),
+                BindingChild::Group(group) => {
+                    for child in group.sub_groups.iter().flat_map(|s| &s.children) {
+                        match child {
+                            Child::Text(text) => ids.push(text.message.clone()),
+                            Child::Image(image) => ids.push(image.alt_message.clone()),
+                        }
+                    }
+                }
+                BindingChild::Progress(_) => {}
+            }
+        }
+        ids.extend(
+            self.actions
+                .actions
+                .iter()
+                .map(|action| action.content_message.clone()),
+        );
+        for input in &self.actions.inputs {
+            match input {
+                Input::Text {
+                    title_message,
+                    place_holder_message,
+                    ..
+                } => {
+                    ids.push(title_message.clone());
+                    ids.push(place_holder_message.clone());
+                }
+                Input::Selection { title_message, .. } => ids.push(title_message.clone()),
+            }
+        }
+        ids
+    }
+
+    /// [`Toast::message_ids`], to set them.
+    pub(crate) fn message_ids_mut(&mut self) -> Vec<&mut Option<String>> {
+        let mut ids = Vec::new();
+        for child in &mut self.visual.binding.children {
+            match child {
+                BindingChild::Text(text) => ids.push(&mut text.message),
+                BindingChild::Image(image) => ids.push(&mut image.alt_message),
+                BindingChild::Group(group) => {
+                    for child in group.sub_groups.iter_mut().flat_map(|s| &mut s.children) {
+                        match child {
+                            Child::Text(text) => ids.push(&mut text.message),
+                            Child::Image(image) => ids.push(&mut image.alt_message),
+                        }
+                    }
+                }
+                BindingChild::Progress(_) => {}
+            }
+        }
+        ids.extend(
+            self.actions
+                .actions
+                .iter_mut()
+                .map(|action| &mut action.content_message),
+        );
+        for input in &mut self.actions.inputs {
+            match input {
+                Input::Text {
+                    title_message,
+                    place_holder_message,
+                    ..
+                } => {
+                    ids.push(title_message);
+                    ids.push(place_holder_message);
+                }
+                Input::Selection { title_message, .. } => ids.push(title_message),
+            }
+        }
+        ids
+    }
+}
+
+fn visit_text(text: &mut Text, f: &mut dyn FnMut(&str, Slot)) {
+    if let Some(id) = &text.message {
+        f(id, Slot::Text(&mut text.text));
+    }
+}
+
+fn visit_image(image: &mut Image, f: &mut dyn FnMut(&str, Slot)) {
+    if let Some(id) = &image.alt_message {
+        f(id, Slot::Text(&mut image.alt));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::localize::{Localizer, MessageTables};
+    use crate::tags::action::Action;
+    use crate::tags::input::Input;
+    use crate::tags::text::Text;
+    use crate::Toast;
+
+    #[test]
+    fn falls_back_through_locales() {
+        let mut messages = MessageTables::new();
+        messages.add_table("de", [("title", "Neue Nachricht"), ("reply", "Antworten")]);
+        messages.insert("de-AT", "title", "Neue Nachricht aus Wien");
+        messages.insert("en", "placeholder", "Type a reply");
+
+        let localizer = Localizer::new(&messages, "de-AT").fallback("en");
+        assert_eq!(localizer.locales(), ["de-AT", "de", "en"]);
+
+        let mut toast = Toast::new().unwrap();
+        toast.title_message("title").unwrap();
+        toast
+            .add_input(Input::new_text("reply", None::<String>).place_holder_message("placeholder"))
+            .unwrap();
+        toast
+            .add_action(Action::new("".into(), "reply").content_message("reply"))
+            .unwrap();
+        toast.localize(&localizer).unwrap();
+
+        let raw = toast.into_raw().unwrap();
+        // The messages come from three locales, so none of them is the language of the toast.
+        assert!(raw.contains("<visual>"));
+        assert!(raw.contains("Neue Nachricht aus Wien"));
+        assert!(raw.contains(r#"placeHolderContent="Type a reply""#));
+        assert!(raw.contains(r#"content="Antworten""#));
+
+        toast.add_text(Text::new("").message("unknown")).unwrap();
+        let Err(Error::MissingMessages { ids, .. }) = toast.localize(&localizer) else {
+            panic!("unknown is in no locale");
+        };
+        assert_eq!(ids, ["unknown"]);
+    }
+
+    #[test]
+    fn uses_the_fallback_language() {
+        let mut messages = MessageTables::new();
+        messages.add_table("en", [("title", "New message"), ("body", "Open to read")]);
+        messages.insert("fr", "unused", "Inutilisé");
+        let localizer = Localizer::new(&messages, "fr-FR").fallback("en");
+        assert_eq!(
+            localizer.resolve("title"),
+            Some(("en", "New message".to_string()))
+        );
+
+        let mut toast = Toast::new().unwrap();
+        toast.title_message("title").unwrap();
+        toast.add_text(Text::new("").message("body")).unwrap();
+        toast.localize(&localizer).unwrap();
+
+        let raw = toast.into_raw().unwrap();
+        assert!(raw.contains(r#"<visual lang="en">"#));
+        assert!(raw.contains(r#"<text id="0">New message</text>"#));
+        assert!(raw.contains("Open to read"));
+    }
+
+    #[cfg(feature = "fluent")]
+    #[test]
+    fn resolves_fluent_messages() {
+        let mut messages = crate::localize::FluentMessages::new();
+        messages
+            .add_resource("en", "title = New message\nreply = Reply")
+            .unwrap();
+        messages
+            .add_resource("fr", "title = Nouveau message")
+            .unwrap();
+
+        let localizer = Localizer::new(messages, "fr-FR").fallback("en");
+        assert_eq!(localizer.message("title").unwrap(), "Nouveau message");
+        assert_eq!(localizer.message("reply").unwrap(), "Reply");
+        assert!(localizer.message("unknown").is_none());
+    }
+}
@@ -0,0 +1,172 @@
+//! Showing toasts for an app, with one notifier set up once and shared by every thread that shows toasts.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use std::thread;
+//! # use windows_notifier::{backend::MemoryBackend, notifier::Notifier, Toast};
+//! let backend = Arc::new(MemoryBackend::new());
+//! let notifier = Notifier::with_backend(backend.clone(), "Contoso.Builds");
+//!
+//! thread::scope(|scope| {
+//!     for build in 0..4 {
+//!         let notifier = notifier.clone();
+//!         scope.spawn(move || {
+//!             let mut toast = Toast::new()?;
+//!             toast.title(&format!("Build {} finished", build))?;
+//!             notifier.show(&toast)
+//!         });
+//!     }
+//! });
+//! assert_eq!(backend.toasts().len(), 4);
+//! # Ok::<(), windows_notifier::error::Error>(())
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::backend::{self, Backend, NotificationData, ShownToast, UpdateResult};
+use crate::error::Result;
+use crate::events::{EventSender, ToastOutcome};
+use crate::history::ToastHistory;
+use crate::identity::{ToastHandle, ToastIdentity};
+use crate::Toast;
+
+/// Shows toasts for one app with one backend.
+///
+/// Cloning is cheap and clones share the backend, so one notifier can be handed to every worker.
+#[derive(Clone)]
+pub struct Notifier {
+    backend: Arc<dyn Backend>,
+    app_id: Arc<str>,
+}
+
+impl fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notifier")
+            .field("app_id", &self.app_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Notifier {
+    /// This can be used if you do not have a AppUserModelID.
+    ///
+    /// However, the toast will erroniously report its origin as powershell.
+    pub const POWERSHELL_APP_ID: &'static str = "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\
+                                                 \\WindowsPowerShell\\v1.0\\powershell.exe";
+
+    /// A notifier showing the toasts of the AppUserModelID `app_id` on this platform.
+    ///
+    /// Only supported on windows, on other platforms showing a toast returns [`Error::Unsupported`](crate::error::Error::Unsupported).
+    pub fn new(app_id: &str) -> Notifier {
+        Notifier::with_backend(backend::platform(), app_id)
+    }
+
+    /// A notifier showing the toasts of `app_id` with `backend`, like a [`MemoryBackend`](backend::MemoryBackend) in tests.
+    pub fn with_backend(backend: Arc<dyn Backend>, app_id: &str) -> Notifier {
+        Notifier {
+            backend,
+            app_id: app_id.into(),
+        }
+    }
+
+    pub fn get_app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    pub(crate) fn backend(&self) -> &Arc<dyn Backend> {
+        &self.backend
+    }
+
+    /// Show `toast`, replacing the shown toast with the same [identity](Toast::identity).
+    pub fn show<S, C>(&self, toast: &Toast<S, C>) -> Result<ToastHandle> {
+        let (sender, events) = EventSender::channel();
+        let shown = self.backend.show(&self.app_id, &toast.payload()?, sender)?;
+        Ok(ToastHandle::new(
+            self.backend.clone(),
+            &self.app_id,
+            toast.get_identity(),
+            events,
+            shown,
+        ))
+    }
+
+    /// Show every toast of `toasts` in order, returning a result for each,
+    /// so a toast that fails does not keep the others from showing.
+    pub fn show_all<'t, S: 't, C: 't>(
+        &self,
+        toasts: impl IntoIterator<Item = &'t Toast<S, C>>,
+    ) -> Vec<Result<ToastHandle>> {
+        toasts.into_iter().map(|toast| self.show(toast)).collect()
+    }
+
+    /// Show `toast` and wait at most `timeout` for the user to activate or dismiss it,
+    /// like `notify-send --wait`.
+    pub fn show_and_wait<S, C>(
+        &self,
+        toast: &Toast<S, C>,
+        timeout: Duration,
+    ) -> Result<ToastOutcome> {
+        self.show(toast)?.events().wait(timeout)
+    }
+
+    /// [`Notifier::show_and_wait`] without blocking the async runtime.
+    pub async fn show_and_wait_async<S, C>(
+        &self,
+        toast: &Toast<S, C>,
+        timeout: Duration,
+    ) -> Result<ToastOutcome> {
+        self.show(toast)?.into_events().wait_async(timeout).await
+    }
+
+    /// Change the `{key}` bindings of the shown toast with `identity`, see [`ToastHandle::update`].
+    pub fn update(
+        &self,
+        identity: &ToastIdentity,
+        data: &NotificationData,
+    ) -> Result<UpdateResult> {
+        let (_, events) = EventSender::channel();
+        ToastHandle::new(
+            self.backend.clone(),
+            &self.app_id,
+            identity,
+            events,
+            ShownToast::default(),
+        )
+        .update(data)
+    }
+
+    /// The toasts of the app in the Action Center.
+    pub fn history(&self) -> ToastHistory {
+        ToastHistory::with_backend(self.backend.clone(), &self.app_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::backend::MemoryBackend;
+    use crate::notifier::Notifier;
+    use crate::Toast;
+
+    #[test]
+    fn shows_all_with_one_app_id() {
+        fn shared<T: Send + Sync + Clone>() {}
+        shared::<Notifier>();
+
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Chat");
+        let mut tagged = Toast::new().unwrap();
+        tagged.tag("thread-42").unwrap();
+        let results = notifier.show_all([&tagged, &Toast::new().unwrap(), &tagged]);
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(Result::is_ok));
+
+        let toasts = backend.toasts();
+        assert_eq!(toasts.len(), 2);
+        assert!(toasts.iter().all(|toast| toast.app_id == "Contoso.Chat"));
+        assert_eq!(notifier.history().list().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,584 @@
+//! Showing toasts from async code, through a bounded queue delivered by a dedicated thread.
+//!
+//! [`DeliveryQueue::show`] only waits for the worker thread, so an async service showing many toasts
+//! does not stall an executor thread on the backend. Any runtime can await it.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use windows_notifier::{backend::MemoryBackend, notifier::Notifier, queue::*, Toast};
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> windows_notifier::error::Result<()> {
+//! # let backend = Arc::new(MemoryBackend::new());
+//! let notifier = Notifier::with_backend(backend.clone(), "Contoso.Builds");
+//! let queue = DeliveryQueue::new(&notifier, 16)?.backpressure(Backpressure::DropOldest);
+//!
+//! let mut toast = Toast::new()?;
+//! toast.title("Build finished")?;
+//! let handle = queue.show(&toast).await?;
+//! # assert!(handle.events().try_recv().is_none());
+//!
+//! // toasts still in the queue are shown before shutdown returns
+//! queue.shutdown().await;
+//! # assert_eq!(backend.toasts().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+
+use crate::backend::{ShownToast, ToastPayload};
+use crate::error::{Error, Result};
+use crate::events::EventSender;
+use crate::identity::ToastHandle;
+use crate::notifier::Notifier;
+use crate::utils::blocking::{oneshot, spawn_blocking, Completer};
+use crate::Toast;
+
+/// What [`DeliveryQueue::show`] does when the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Backpressure {
+    /// Default value. Wait until the worker takes a toast out of the queue.
+    #[default]
+    Wait,
+    /// Drop the toast that was queued first, its `show` returns [`Error::QueueFull`].
+    DropOldest,
+    /// Return [`Error::QueueFull`] without queueing the toast.
+    Error,
+}
+
+/// A toast waiting for the worker, and the `show` waiting for it to be shown.
+struct Delivery {
+    payload: ToastPayload,
+    events: EventSender,
+    shown: Completer<Result<ShownToast>>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    deliveries: VecDeque<Delivery>,
+    /// The `show` calls waiting for room in the queue, one waker for each by its id.
+    waiting: Vec<(u64, Waker)>,
+    next_waiter: u64,
+    closed: bool,
+}
+
+impl QueueState {
+    fn wake_waiting(&mut self) {
+        for (_, waker) in self.waiting.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The state shared with the worker.
+#[derive(Default)]
+struct Shared {
+    state: Mutex<QueueState>,
+    queued: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        state.wake_waiting();
+        self.queued.notify_all();
+    }
+}
+
+/// Closes the queue once the last clone is dropped, the worker still shows what is queued.
+struct Worker {
+    shared: Arc<Shared>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
+
+/// Shows the toasts of a [`Notifier`] on a dedicated thread, see the [module](self) docs.
+///
+/// Cloning is cheap and clones share the queue and its worker.
+#[derive(Clone)]
+pub struct DeliveryQueue {
+    notifier: Notifier,
+    worker: Arc<Worker>,
+    capacity: usize,
+    backpressure: Backpressure,
+}
+
+impl fmt::Debug for DeliveryQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeliveryQueue")
+            .field("notifier", &self.notifier)
+            .field("capacity", &self.capacity)
+            .field("backpressure", &self.backpressure)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DeliveryQueue {
+    /// A queue of at most `capacity` toasts, shown with `notifier` by a new worker thread.
+    pub fn new(notifier: &Notifier, capacity: usize) -> Result<DeliveryQueue> {
+        if capacity == 0 {
+            return Err(Error::invalid_argument("capacity", "must be at least 1"));
+        }
+        let shared = Arc::new(Shared::default());
+        let thread = thread::spawn({
+            let notifier = notifier.clone();
+            let shared = shared.clone();
+            move || deliver(&notifier, &shared)
+        });
+        Ok(DeliveryQueue {
+            notifier: notifier.clone(),
+            worker: Arc::new(Worker {
+                shared,
+                thread: Mutex::new(Some(thread)),
+            }),
+            capacity,
+            backpressure: Backpressure::default(),
+        })
+    }
+
+    /// What `show` does when the queue is full, [`Backpressure::Wait`] by default.
+    pub fn backpressure(mut self, backpressure: Backpressure) -> Self {
+        self.backpressure = backpressure;
+        self
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn get_backpressure(&self) -> Backpressure {
+        self.backpressure
+    }
+
+    pub fn get_notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+
+    /// The number of toasts waiting for the worker.
+    pub fn pending(&self) -> usize {
+        self.worker.shared.lock().deliveries.len()
+    }
+
+    /// Queue `toast` and complete once the worker has shown it, like [`Notifier::show`].
+    ///
+    /// The toast is read and queued when `show` is called, so it can be changed or dropped while the future is pending,
+    /// and toasts are shown in the order of the calls even if the futures are awaited later.
+    /// Only a toast that has to [wait](Backpressure::Wait) for room is queued by the future, once it is polled.
+    /// Returns [`Error::QueueClosed`] once the queue is [shut down](DeliveryQueue::shutdown),
+    /// or if the worker stopped because the backend panicked.
+    pub fn show<S, C>(
+        &self,
+        toast: &Toast<S, C>,
+    ) -> impl Future<Output = Result<ToastHandle>> + Send + 'static {
+        let (sender, events) = EventSender::channel();
+        let (completer, shown) = oneshot();
+        let queued = toast.payload().map(|payload| {
+            let identity = payload.identity.clone();
+            let mut push = Push {
+                queue: self.clone(),
+                delivery: Some(Delivery {
+                    payload,
+                    events: sender,
+                    shown: completer,
+                }),
+                waiter: None,
+            };
+            let offered = push.offer(None);
+            (identity, push, offered)
+        });
+        let notifier = self.notifier.clone();
+        async move {
+            let (identity, push, offered) = queued?;
+            match offered {
+                Poll::Ready(result) => result?,
+                Poll::Pending => push.await?,
+            }
+            // The worker only drops a delivery without completing it once it stopped.
+            let shown = shown.await.map_err(|_| Error::QueueClosed)??;
+            Ok(ToastHandle::new(
+                notifier.backend().clone(),
+                notifier.get_app_id(),
+                &identity,
+                events,
+                shown,
+            ))
+        }
+    }
+
+    fn full(&self) -> Error {
+        Error::QueueFull {
+            capacity: self.capacity,
+        }
+    }
+
+    /// Stop taking new toasts and complete once every queued toast was shown and the worker stopped.
+    ///
+    /// Shutting down is shared by all clones, later calls complete right away.
+    pub async fn shutdown(&self) {
+        self.worker.shared.close();
+        let thread = self
+            .worker
+            .thread
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(thread) = thread {
+            // The worker only panics if the backend did, which was already reported on its thread.
+            let _ = spawn_blocking(move || thread.join()).await;
+        }
+    }
+}
+
+/// Adds a delivery to the queue, making room for it as set by the [backpressure](DeliveryQueue::backpressure).
+struct Push {
+    queue: DeliveryQueue,
+    delivery: Option<Delivery>,
+    /// The id of the waker while waiting for room.
+    waiter: Option<u64>,
+}
+
+impl Push {
+    /// Queue the delivery if there is room, otherwise wait with `waker`, if there is one.
+    fn offer(&mut self, waker: Option<&Waker>) -> Poll<Result<()>> {
+        let queue = &self.queue;
+        let shared = &queue.worker.shared;
+        let mut state = shared.lock();
+        if state.closed {
+            return Poll::Ready(Err(Error::QueueClosed));
+        }
+        if state.deliveries.len() >= queue.capacity {
+            match queue.backpressure {
+                Backpressure::Wait => {
+                    if let Some(waker) = waker {
+                        let waiter = *self.waiter.get_or_insert_with(|| {
+                            state.next_waiter += 1;
+                            state.next_waiter
+                        });
+                        match state.waiting.iter_mut().find(|(id, _)| *id == waiter) {
+                            Some((_, waiting)) => waiting.clone_from(waker),
+                            None => state.waiting.push((waiter, waker.clone())),
+                        }
+                    }
+                    return Poll::Pending;
+                }
+                Backpressure::DropOldest => {
+                    if let Some(dropped) = state.deliveries.pop_front() {
+                        dropped.shown.complete(Err(queue.full()));
+                    }
+                }
+                Backpressure::Error => return Poll::Ready(Err(queue.full())),
+            }
+        }
+        state.deliveries.extend(self.delivery.take());
+        shared.queued.notify_one();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Future for Push {
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut().offer(Some(cx.waker()))
+    }
+}
+
+impl Drop for Push {
+    /// A `show` that is dropped while waiting gives up its place.
+    fn drop(&mut self) {
+        if let Some(waiter) = self.waiter {
+            let mut state = self.queue.worker.shared.lock();
+            state.waiting.retain(|(id, _)| *id != waiter);
+        }
+    }
+}
+
+/// Closes the queue once the worker stops, even when the backend panicked,
+/// so the queued and waiting `show` calls return [`Error::QueueClosed`] instead of waiting forever.
+struct Stopped<'a>(&'a Shared);
+
+impl Drop for Stopped<'_> {
+    fn drop(&mut self) {
+        self.0.close();
+        // Dropping the completers completes their `show` calls.
+        let deliveries = mem::take(&mut self.0.lock().deliveries);
+        drop(deliveries);
+    }
+}
+
+/// The worker, showing the queued toasts in order until the queue is closed and empty.
+fn deliver(notifier: &Notifier, shared: &Shared) {
+    let _stopped = Stopped(shared);
+    loop {
+        let delivery = {
+            let mut state = shared.lock();
+            loop {
+                if let Some(delivery) = state.deliveries.pop_front() {
+                    state.wake_waiting();
+                    break delivery;
+                }
+                if state.closed {
+                    return;
+                }
+                state = shared
+                    .queued
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        };
+        let result =
+            notifier
+                .backend()
+                .show(notifier.get_app_id(), &delivery.payload, delivery.events);
+        delivery.shown.complete(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{poll_fn, Future};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Condvar, Mutex, PoisonError};
+    use std::task::Poll;
+
+    use crate::backend::{
+        Backend, MemoryBackend, NotificationData, ShownToast, ToastPayload, UpdateResult,
+    };
+    use crate::error::{Error, Result};
+    use crate::events::EventSender;
+    use crate::identity::ToastIdentity;
+    use crate::notifier::Notifier;
+    use crate::queue::{Backpressure, DeliveryQueue};
+    use crate::Toast;
+
+    /// Shows toasts only once the gate is opened, so the tests can fill the queue.
+    #[derive(Default)]
+    struct Gated {
+        open: Mutex<bool>,
+        opened: Condvar,
+        shows: AtomicUsize,
+        /// Panic at the gate instead of showing the toast.
+        panics: AtomicBool,
+        memory: MemoryBackend,
+    }
+
+    impl Backend for Gated {
+        fn show(
+            &self,
+            app_id: &str,
+            payload: &ToastPayload,
+            events: EventSender,
+        ) -> Result<ShownToast> {
+            self.shows.fetch_add(1, Ordering::SeqCst);
+            let open = self.open.lock().unwrap_or_else(PoisonError::into_inner);
+            drop(
+                self.opened
+                    .wait_while(open, |open| !*open)
+                    .unwrap_or_else(PoisonError::into_inner),
+            );
+            if self.panics.load(Ordering::SeqCst) {
+                panic!("the backend failed");
+            }
+            self.memory.show(app_id, payload, events)
+        }
+
+        fn update(
+            &self,
+            app_id: &str,
+            identity: &ToastIdentity,
+            data: &NotificationData,
+        ) -> Result<UpdateResult> {
+            self.memory.update(app_id, identity, data)
+        }
+
+        fn hide(&self, app_id: &str, identity: &ToastIdentity, shown: &ShownToast) -> Result<()> {
+            self.memory.hide(app_id, identity, shown)
+        }
+
+        fn remove(&self, app_id: &str, identity: &ToastIdentity) -> Result<()> {
+            self.memory.remove(app_id, identity)
+        }
+
+        fn history(&self, app_id: &str) -> Result<Vec<ToastPayload>> {
+            self.memory.history(app_id)
+        }
+
+        fn remove_group(&self, app_id: &str, group: &str) -> Result<()> {
+            self.memory.remove_group(app_id, group)
+        }
+
+        fn clear(&self, app_id: &str) -> Result<()> {
+            self.memory.clear(app_id)
+        }
+    }
+
+    impl Gated {
+        fn open(&self) {
+            *self.open.lock().unwrap() = true;
+            self.opened.notify_all();
+        }
+    }
+
+    fn toast(title: &str) -> Toast {
+        let mut toast = Toast::new().unwrap();
+        toast.title(title).unwrap();
+        toast
+    }
+
+    async fn until(mut done: impl FnMut() -> bool) {
+        while !done() {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn applies_backpressure() {
+        fn spawnable<F: Future + Send + 'static>(future: F) -> F {
+            future
+        }
+
+        let backend = Arc::new(Gated::default());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Builds");
+        assert!(DeliveryQueue::new(&notifier, 0).is_err());
+        let queue = DeliveryQueue::new(&notifier, 1).unwrap();
+
+        // the worker takes the first toast and waits at the gate
+        let first = tokio::spawn(spawnable(queue.show(&toast("first"))));
+        until(|| backend.shows.load(Ordering::SeqCst) == 1).await;
+        let second = tokio::spawn(queue.show(&toast("second")));
+        until(|| queue.pending() == 1).await;
+
+        let dropping = queue.clone().backpressure(Backpressure::DropOldest);
+        let third = tokio::spawn(dropping.show(&toast("third")));
+        assert!(matches!(
+            second.await.unwrap(),
+            Err(Error::QueueFull { capacity: 1 })
+        ));
+        let errors = queue.clone().backpressure(Backpressure::Error);
+        assert!(matches!(
+            errors.show(&toast("fourth")).await,
+            Err(Error::QueueFull { capacity: 1 })
+        ));
+        let fifth = tokio::spawn(queue.show(&toast("fifth")));
+        tokio::task::yield_now().await;
+
+        backend.open();
+        for shown in [first, third, fifth] {
+            assert_eq!(shown.await.unwrap().unwrap().get_app_id(), "Contoso.Builds");
+        }
+        queue.shutdown().await;
+        assert!(matches!(
+            queue.show(&toast("late")).await,
+            Err(Error::QueueClosed)
+        ));
+        let toasts = backend.memory.toasts();
+        assert_eq!(toasts.len(), 3);
+        for (toast, title) in toasts.iter().zip(["first", "third", "fifth"]) {
+            assert!(toast.payload.xml.contains(title));
+        }
+    }
+
+    #[tokio::test]
+    async fn queues_on_call_and_waits_once() {
+        let backend = Arc::new(Gated::default());
+        let queue = DeliveryQueue::new(
+            &Notifier::with_backend(backend.clone(), "Contoso.Builds"),
+            1,
+        )
+        .unwrap();
+        // neither future is polled, the worker gets the first toast and the second waits in the queue
+        let first = queue.show(&toast("first"));
+        until(|| backend.shows.load(Ordering::SeqCst) == 1).await;
+        let second = queue.show(&toast("second"));
+        assert_eq!(queue.pending(), 1);
+
+        let mut third = Box::pin(queue.show(&toast("third")));
+        for _ in 0..3 {
+            let polled = poll_fn(|cx| Poll::Ready(third.as_mut().poll(cx))).await;
+            assert!(polled.is_pending());
+        }
+        assert_eq!(queue.worker.shared.lock().waiting.len(), 1);
+        drop(third);
+        assert!(queue.worker.shared.lock().waiting.is_empty());
+
+        backend.open();
+        first.await.unwrap();
+        second.await.unwrap();
+        queue.shutdown().await;
+        assert_eq!(backend.memory.toasts().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn shutdown_flushes_queued_toasts() {
+        let backend = Arc::new(Gated::default());
+        let queue = DeliveryQueue::new(
+            &Notifier::with_backend(backend.clone(), "Contoso.Builds"),
+            8,
+        )
+        .unwrap();
+        let shown: Vec<_> = (0..8)
+            .map(|i| tokio::spawn(queue.show(&toast(&i.to_string()))))
+            .collect();
+        until(|| backend.shows.load(Ordering::SeqCst) == 1 && queue.pending() == 7).await;
+
+        let shutdown = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.shutdown().await }
+        });
+        until(|| queue.worker.shared.lock().closed).await;
+        backend.open();
+        shutdown.await.unwrap();
+        assert_eq!(backend.memory.toasts().len(), 8);
+        for shown in shown {
+            shown.await.unwrap().unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn closes_when_the_backend_panics() {
+        let backend = Arc::new(Gated {
+            panics: AtomicBool::new(true),
+            ..Gated::default()
+        });
+        let queue = DeliveryQueue::new(
+            &Notifier::with_backend(backend.clone(), "Contoso.Builds"),
+            1,
+        )
+        .unwrap();
+        // one toast at the gate, one queued and one waiting for room
+        let first = tokio::spawn(queue.show(&toast("first")));
+        until(|| backend.shows.load(Ordering::SeqCst) == 1).await;
+        let second = tokio::spawn(queue.show(&toast("second")));
+        until(|| queue.pending() == 1).await;
+        let third = tokio::spawn(queue.show(&toast("third")));
+        until(|| queue.worker.shared.lock().waiting.len() == 1).await;
+
+        backend.open();
+        for shown in [first, second, third] {
+            assert!(matches!(shown.await.unwrap(), Err(Error::QueueClosed)));
+        }
+        queue.shutdown().await;
+        assert!(matches!(
+            queue.show(&toast("late")).await,
+            Err(Error::QueueClosed)
+        ));
+        assert_eq!(backend.shows.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,244 @@
+//! Raw xml elements, used when parsing toasts and to keep anything the typed model does not know about.
+
+use std::io::{BufRead, Write};
+
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+
+use crate::error::{Result, XmlErr};
+use crate::utils::into_raw::{write_children, ToXML};
+
+/// A node inside a [`RawElement`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum RawNode {
+    Element(RawElement),
+    Text(String),
+}
+
+/// An xml element as it was read, with its attributes and children in document order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct RawElement {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: Vec<(String, String)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub children: Vec<RawNode>,
+}
+
+impl RawElement {
+    /// Reads the root element of an xml document.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<RawElement> {
+        let mut reader = Reader::from_reader(reader);
+        let mut buf = Vec::new();
+        let mut stack: Vec<RawElement> = Vec::new();
+
+        loop {
+            let event = reader.read_event_into(&mut buf).map_err(parse_err)?;
+            match event {
+                Event::Start(start) => stack.push(RawElement::from_start(&start)?),
+                Event::Empty(start) => {
+                    let element = RawElement::from_start(&start)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(RawNode::Element(element)),
+                        None => return Ok(element),
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack
+                        .pop()
+                        .ok_or_else(|| parse_err("unexpected closing tag"))?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(RawNode::Element(element)),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(parent) = stack.last_mut() {
+                        let text = text.unescape().map_err(parse_err)?;
+                        parent.children.push(RawNode::Text(text.into_owned()));
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(parent) = stack.last_mut() {
+                        let text = String::from_utf8_lossy(&data.into_inner()).into_owned();
+                        parent.children.push(RawNode::Text(text));
+                    }
+                }
+                Event::Eof => return Err(parse_err("no root element")),
+                Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => {}
+            }
+            buf.clear();
+        }
+    }
+
+    fn from_start(start: &BytesStart) -> Result<RawElement> {
+        let mut attributes = Vec::new();
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(parse_err)?;
+            let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+            let value = attribute.unescape_value().map_err(parse_err)?.into_owned();
+            attributes.push((key, value));
+        }
+        Ok(RawElement {
+            name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+            attributes,
+            children: Vec::new(),
+        })
+    }
+
+    /// The child elements, skipping text.
+    pub fn elements(&self) -> impl Iterator<Item = &RawElement> {
+        self.children.iter().filter_map(|child| match child {
+            RawNode::Element(element) => Some(element),
+            RawNode::Text(_) => None,
+        })
+    }
+
+    /// All the text directly inside this element.
+    pub fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|child| match child {
+                RawNode::Text(text) => Some(text.as_str()),
+                RawNode::Element(_) => None,
+            })
+            .collect()
+    }
+
+    /// Whether the element has any text that is not just whitespace.
+    pub(crate) fn has_text(&self) -> bool {
+        !self.text().trim().is_empty()
+    }
+}
+
+impl ToXML for RawElement {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut start = BytesStart::new(self.name.as_str());
+        for (key, value) in &self.attributes {
+            start.push_attribute((key.as_str(), value.as_str()));
+        }
+        if self.children.is_empty() {
+            writer.write_event(Event::Empty(start))?;
+            return Ok(());
+        }
+        write_children(writer, start, |writer| {
+            for child in &self.children {
+                match child {
+                    RawNode::Element(element) => element.write_xml(writer)?,
+                    RawNode::Text(text) => writer.write_event(Event::Text(BytesText::new(text)))?,
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Attributes and child elements that were not understood when parsing, written back out unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
+pub struct Extra {
+    pub(crate) attributes: Vec<(String, String)>,
+    pub(crate) elements: Vec<RawElement>,
+}
+
+impl Extra {
+    /// The attributes that were not understood.
+    pub fn attributes(&self) -> &[(String, String)] {
+        &self.attributes
+    }
+
+    /// The child elements that were not understood.
+    pub fn elements(&self) -> &[RawElement] {
+        &self.elements
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.attributes.is_empty() && self.elements.is_empty()
+    }
+
+    pub(crate) fn push_attributes(&self, start: &mut BytesStart) {
+        for (key, value) in &self.attributes {
+            start.push_attribute((key.as_str(), value.as_str()));
+        }
+    }
+
+    pub(crate) fn write_elements<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        for element in &self.elements {
+            element.write_xml(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Takes the known attributes of a [`RawElement`], leaving the rest for [`Extra`].
+pub(crate) struct Attributes {
+    attributes: Vec<(String, String)>,
+}
+
+impl Attributes {
+    pub(crate) fn new(element: &RawElement) -> Attributes {
+        Attributes {
+            attributes: element.attributes.clone(),
+        }
+    }
+
+    /// Removes and returns the attribute called `name`.
+    pub(crate) fn take(&mut self, name: &str) -> Option<String> {
+        let index = self.attributes.iter().position(|(key, _)| key == name)?;
+        Some(self.attributes.remove(index).1)
+    }
+
+    /// Removes and parses the attribute called `name`, leaving it in place if `parse` does not understand it.
+    pub(crate) fn take_with<T>(
+        &mut self,
+        name: &str,
+        parse: impl FnOnce(&str) -> Option<T>,
+    ) -> Option<T> {
+        let index = self.attributes.iter().position(|(key, _)| key == name)?;
+        let value = parse(&self.attributes[index].1)?;
+        self.attributes.remove(index);
+        Some(value)
+    }
+
+    /// Removes a `"true"` / `"false"` attribute, defaulting to false.
+    pub(crate) fn take_bool(&mut self, name: &str) -> bool {
+        self.take_with(name, |value| match value {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        })
+        .unwrap_or(false)
+    }
+
+    /// The attributes that were not taken, together with the child `elements` that were not understood.
+    pub(crate) fn into_extra(self, elements: Vec<RawElement>) -> Extra {
+        Extra {
+            attributes: self.attributes,
+            elements,
+        }
+    }
+}
+
+/// Typed toast content that can be read back from a [`RawElement`].
+pub(crate) trait FromXML: Sized {
+    /// Returns `None` if the element can not be represented, in which case the caller keeps it raw.
+    fn from_raw(element: &RawElement) -> Option<Self>;
+}
+
+fn parse_err(err: impl std::fmt::Display) -> crate::error::Error {
+    XmlErr::Parse(err.to_string()).into()
+}
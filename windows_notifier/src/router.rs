@@ -0,0 +1,249 @@
+//! Routing activations to handlers by their arguments, instead of matching the argument strings by hand.
+//!
+//! A route matches an argument value, like `action=reply`, or the id of the toast [`Header`].
+//! The first matching route handles the activation, in the order the routes were added.
+//!
+//! ```
+//! # use std::collections::HashMap;
+//! # use windows_notifier::{arguments::ToastArguments, router::ActivationRouter};
+//! enum Response {
+//!     Replied(String),
+//!     Opened(u64),
+//! }
+//!
+//! let router = ActivationRouter::new()
+//!     .route("action", "reply", |activation| {
+//!         Ok(Response::Replied(activation.get_input("reply").unwrap_or_default().into()))
+//!     })
+//!     .route("action", "open", |activation| {
+//!         Ok(Response::Opened(activation.get_arguments().get_parsed("threadId")?))
+//!     });
+//!
+//! let arguments = ToastArguments::new().add("action", "open").add("threadId", 92187);
+//! let response = router.dispatch(&arguments.to_string(), &HashMap::new())?;
+//! assert!(matches!(response, Some(Response::Opened(92187))));
+//! # Ok::<(), windows_notifier::error::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::arguments::ToastArguments;
+use crate::error::Result;
+use crate::events::ToastEvent;
+use crate::tags::header::Header;
+use crate::Toast;
+
+/// An activation as the handlers see it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activation {
+    arguments: ToastArguments,
+    user_inputs: HashMap<String, String>,
+    header_id: Option<String>,
+}
+
+impl Activation {
+    /// The parsed arguments of the activation.
+    pub fn get_arguments(&self) -> &ToastArguments {
+        &self.arguments
+    }
+
+    /// The value of the input with `id`.
+    pub fn get_input(&self, id: &str) -> Option<&str> {
+        self.user_inputs.get(id).map(String::as_str)
+    }
+
+    /// The values of the inputs by their id.
+    pub fn user_inputs(&self) -> &HashMap<String, String> {
+        &self.user_inputs
+    }
+
+    /// The id of the header, if the header was clicked.
+    pub fn get_header_id(&self) -> Option<&str> {
+        self.header_id.as_deref()
+    }
+}
+
+type Handler<R> = Box<dyn Fn(&Activation) -> Result<R> + Send + Sync>;
+
+enum Pattern {
+    Argument { key: String, value: String },
+    Header(String),
+}
+
+impl Pattern {
+    fn matches(&self, activation: &Activation) -> bool {
+        match self {
+            Pattern::Argument { key, value } => {
+                activation.arguments.get(key) == Some(value.as_str())
+            }
+            Pattern::Header(id) => activation.header_id.as_deref() == Some(id.as_str()),
+        }
+    }
+}
+
+/// Handlers for activations, each returning an `R`.
+pub struct ActivationRouter<R> {
+    routes: Vec<(Pattern, Handler<R>)>,
+    fallback: Option<Handler<R>>,
+}
+
+impl<R> fmt::Debug for ActivationRouter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActivationRouter")
+            .field("routes", &self.routes.len())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl<R> Default for ActivationRouter<R> {
+    fn default() -> Self {
+        ActivationRouter::new()
+    }
+}
+
+impl<R> ActivationRouter<R> {
+    pub fn new() -> ActivationRouter<R> {
+        ActivationRouter {
+            routes: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    /// Handle activations whose argument `key` is `value`.
+    pub fn route(
+        mut self,
+        key: &str,
+        value: &str,
+        handler: impl Fn(&Activation) -> Result<R> + Send + Sync + 'static,
+    ) -> Self {
+        let pattern = Pattern::Argument {
+            key: key.into(),
+            value: value.into(),
+        };
+        self.routes.push((pattern, Box::new(handler)));
+        self
+    }
+
+    /// Handle clicks on the header with `id`, see [`ActivationRouter::dispatch_event`].
+    pub fn header(
+        mut self,
+        id: &str,
+        handler: impl Fn(&Activation) -> Result<R> + Send + Sync + 'static,
+    ) -> Self {
+        self.routes
+            .push((Pattern::Header(id.into()), Box::new(handler)));
+        self
+    }
+
+    /// Handle the activations no route matches.
+    pub fn fallback(
+        mut self,
+        handler: impl Fn(&Activation) -> Result<R> + Send + Sync + 'static,
+    ) -> Self {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Handle an activation with `arguments` and `user_inputs`.
+    ///
+    /// Returns `None` if no route matches and there is no fallback,
+    /// and [`Error::ActivationParse`](crate::error::Error::ActivationParse) if the arguments do not parse.
+    pub fn dispatch(
+        &self,
+        arguments: &str,
+        user_inputs: &HashMap<String, String>,
+    ) -> Result<Option<R>> {
+        self.handle(Activation {
+            arguments: arguments.parse()?,
+            user_inputs: user_inputs.clone(),
+            header_id: None,
+        })
+    }
+
+    /// Handle an event of `toast`, `None` for events that are not activations.
+    ///
+    /// Windows activates a header click with the arguments of the [`Header`],
+    /// so an activation with those arguments is routed to the header routes first.
+    pub fn dispatch_event<S, C>(
+        &self,
+        toast: &Toast<S, C>,
+        event: &ToastEvent,
+    ) -> Result<Option<R>> {
+        let ToastEvent::Activated {
+            arguments,
+            user_inputs,
+        } = event
+        else {
+            return Ok(None);
+        };
+        let header_id = toast
+            .header()
+            .filter(|header| header.get_arguments() == arguments)
+            .map(Header::get_id);
+        self.handle(Activation {
+            arguments: arguments.parse()?,
+            user_inputs: user_inputs.clone(),
+            header_id: header_id.map(Into::into),
+        })
+    }
+
+    fn handle(&self, activation: Activation) -> Result<Option<R>> {
+        let handler = self
+            .routes
+            .iter()
+            .find(|(pattern, _)| pattern.matches(&activation))
+            .map(|(_, handler)| handler)
+            .or(self.fallback.as_ref());
+        handler.map(|handler| handler(&activation)).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::events::ToastEvent;
+    use crate::router::ActivationRouter;
+    use crate::tags::header::Header;
+    use crate::Toast;
+
+    #[test]
+    fn routes_header_and_arguments() {
+        let router = ActivationRouter::new()
+            .header("6289", |activation| {
+                Ok(format!("header {}", activation.get_arguments()))
+            })
+            .route("action", "reply", |activation| {
+                Ok(format!("reply {}", activation.get_input("reply").unwrap()))
+            })
+            .fallback(|_| Ok("fallback".to_string()));
+
+        let mut toast = Toast::new().unwrap();
+        toast
+            .add_header(Header::new("6289", "Camping!!", "action=openConversation"))
+            .unwrap();
+        let activate = |arguments: &str| ToastEvent::Activated {
+            arguments: arguments.into(),
+            user_inputs: HashMap::from([("reply".to_string(), "Sure".to_string())]),
+        };
+
+        let header = router
+            .dispatch_event(&toast, &activate("action=openConversation"))
+            .unwrap();
+        assert_eq!(header.as_deref(), Some("header action=openConversation"));
+        let reply = router
+            .dispatch_event(&toast, &activate("action=reply"))
+            .unwrap();
+        assert_eq!(reply.as_deref(), Some("reply Sure"));
+        let fallback = router.dispatch("other", &HashMap::new()).unwrap();
+        assert_eq!(fallback.as_deref(), Some("fallback"));
+
+        assert!(router.dispatch("a=%", &HashMap::new()).is_err());
+        assert!(ActivationRouter::<()>::new()
+            .dispatch("action=reply", &HashMap::new())
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,527 @@
+//! Toasts that are shown later, kept in a [`ScheduleStore`] so they survive restarts of the app.
+//! With the `serde` feature, `FileStore` keeps them in a JSON file.
+//!
+//! The [`Scheduler`] does not run a thread of its own, the app calls [`Scheduler::deliver_due`]
+//! from its event loop or a timer, using [`Scheduler::next_due`] to know when.
+//! This works the same on every platform, unlike the WinRT `ScheduledToastNotification`.
+//! Scheduled toasts are shown without a [handle](crate::identity::ToastHandle), so nobody receives their events,
+//! except for the toasts a [`Snoozer`](crate::snooze::Snoozer) delivers.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use std::time::Duration;
+//! # use windows_notifier::{backend::MemoryBackend, notifier::Notifier, schedule::*, Toast};
+//! let backend = Arc::new(MemoryBackend::new());
+//! let notifier = Notifier::with_backend(backend.clone(), "Contoso.Calendar");
+//! let clock = Arc::new(ManualClock::default());
+//! let scheduler = Scheduler::new(&notifier, MemoryStore::new())?.clock(clock.clone());
+//!
+//! let mut toast = Toast::new()?;
+//! toast.title("Standup in 5 minutes")?;
+//! toast.schedule_in(&scheduler, Duration::from_secs(300))?;
+//!
+//! clock.advance(Duration::from_secs(300));
+//! assert_eq!(scheduler.deliver_due()?.len(), 1);
+//! assert_eq!(backend.toasts().len(), 1);
+//! # Ok::<(), windows_notifier::error::Error>(())
+//! ```
+
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use crate::backend::ToastPayload;
+#[cfg(feature = "serde")]
+use crate::error::Error;
+use crate::error::Result;
+use crate::events::EventSender;
+use crate::notifier::Notifier;
+use crate::Toast;
+
+/// Where the scheduler reads the time from, so tests can use a [`ManualClock`].
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+/// The time of the system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when it is told to, starting at the unix epoch.
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<SystemTime>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new(SystemTime::UNIX_EPOCH)
+    }
+}
+
+impl ManualClock {
+    pub fn new(now: SystemTime) -> ManualClock {
+        ManualClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The id of a scheduled toast, used to [cancel](Scheduler::cancel) it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ScheduleId(u64);
+
+impl fmt::Display for ScheduleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A toast waiting to be shown.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct ScheduledToast {
+    pub id: ScheduleId,
+    /// When the toast is shown.
+    pub due: SystemTime,
+    pub app_id: String,
+    pub payload: ToastPayload,
+    /// How often the toast was [snoozed](crate::snooze) before, 0 for a toast that was scheduled by the app.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub snoozes: u32,
+}
+
+/// Everything a [`ScheduleStore`] keeps.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
+pub struct ScheduleState {
+    /// Ids are never reused, so an old id can not cancel a newer toast.
+    pub next_id: u64,
+    /// The pending toasts, ordered by when they are due.
+    pub toasts: Vec<ScheduledToast>,
+}
+
+/// Keeps the pending toasts of a [`Scheduler`].
+pub trait ScheduleStore: Send + Sync {
+    /// The state saved last, or the default state if nothing was saved yet.
+    fn load(&self) -> Result<ScheduleState>;
+
+    /// Replace the saved state.
+    fn save(&self, state: &ScheduleState) -> Result<()>;
+}
+
+/// Keeps the pending toasts in memory, they are lost when the process ends.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    state: Mutex<ScheduleState>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl ScheduleStore for MemoryStore {
+    fn load(&self) -> Result<ScheduleState> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone())
+    }
+
+    fn save(&self, state: &ScheduleState) -> Result<()> {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = state.clone();
+        Ok(())
+    }
+}
+
+/// Keeps the pending toasts in a JSON file, so they are still shown after the app restarts.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: std::path::PathBuf,
+}
+
+#[cfg(feature = "serde")]
+impl FileStore {
+    /// A store in the file at `path`, which is created on the first save.
+    pub fn new(path: impl Into<std::path::PathBuf>) -> FileStore {
+        FileStore { path: path.into() }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ScheduleStore for FileStore {
+    fn load(&self) -> Result<ScheduleState> {
+        let json = match std::fs::read(&self.path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(ScheduleState::default())
+            }
+            Err(err) => return Err(Error::store(self.path.display(), err)),
+        };
+        serde_json::from_slice(&json).map_err(|err| Error::store(self.path.display(), err))
+    }
+
+    /// Writes a temporary file next to the store and moves it over the store,
+    /// so a crash while saving keeps the previous state.
+    fn save(&self, state: &ScheduleState) -> Result<()> {
+        let json =
+            serde_json::to_vec(state).map_err(|err| Error::store(self.path.display(), err))?;
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, json)
+            .and_then(|_| std::fs::rename(&temporary, &self.path))
+            .map_err(|err| Error::store(self.path.display(), err))
+    }
+}
+
+/// Shows toasts once they are due, see the [module](self) docs.
+pub struct Scheduler {
+    notifier: Notifier,
+    store: Box<dyn ScheduleStore>,
+    clock: Box<dyn Clock>,
+    state: Mutex<ScheduleState>,
+}
+
+impl Scheduler {
+    /// A scheduler showing toasts with `notifier`, starting with the toasts that are pending in `store`.
+    pub fn new(notifier: &Notifier, store: impl ScheduleStore + 'static) -> Result<Self> {
+        let state = store.load()?;
+        Ok(Scheduler {
+            notifier: notifier.clone(),
+            store: Box::new(store),
+            clock: Box::new(SystemClock),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Use `clock` instead of the [`SystemClock`].
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// The current time of the scheduler's clock.
+    pub fn now(&self) -> SystemTime {
+        self.clock.now()
+    }
+
+    /// Show `payload` for the app of the notifier once `due` has passed.
+    pub fn schedule(&self, payload: ToastPayload, due: SystemTime) -> Result<ScheduleId> {
+        self.schedule_snoozed(payload, due, 0)
+    }
+
+    /// [`Scheduler::schedule`] for a toast that was snoozed `snoozes` times.
+    pub(crate) fn schedule_snoozed(
+        &self,
+        payload: ToastPayload,
+        due: SystemTime,
+        snoozes: u32,
+    ) -> Result<ScheduleId> {
+        let mut state = self.lock();
+        let id = ScheduleId(state.next_id);
+        let mut next = state.clone();
+        next.next_id += 1;
+        let index = next.toasts.partition_point(|toast| toast.due <= due);
+        next.toasts.insert(
+            index,
+            ScheduledToast {
+                id,
+                due,
+                app_id: self.notifier.get_app_id().into(),
+                payload,
+                snoozes,
+            },
+        );
+        self.store.save(&next)?;
+        *state = next;
+        Ok(id)
+    }
+
+    /// Remove the toast with `id` before it is shown.
+    /// Returns false if there is no such toast, like when it was already shown.
+    pub fn cancel(&self, id: ScheduleId) -> Result<bool> {
+        let mut state = self.lock();
+        let Some(index) = state.toasts.iter().position(|toast| toast.id == id) else {
+            return Ok(false);
+        };
+        let mut next = state.clone();
+        next.toasts.remove(index);
+        self.store.save(&next)?;
+        *state = next;
+        Ok(true)
+    }
+
+    /// The toasts that are not shown yet, ordered by when they are due.
+    pub fn pending(&self) -> Vec<ScheduledToast> {
+        self.lock().toasts.clone()
+    }
+
+    /// When the next toast is due.
+    pub fn next_due(&self) -> Option<SystemTime> {
+        self.lock().toasts.first().map(|toast| toast.due)
+    }
+
+    /// Show every toast that is due, oldest first, and return their ids.
+    ///
+    /// The toasts are removed from the store before they are shown, so a toast is never shown twice,
+    /// not even when the store fails. If the store fails, nothing is shown.
+    /// If the backend fails, the toast it failed on and the ones after it stay pending.
+    pub fn deliver_due(&self) -> Result<Vec<ScheduleId>> {
+        self.deliver_due_with(|_| EventSender::discard())
+    }
+
+    /// [`Scheduler::deliver_due`], sending the events of each toast to the sender `events` returns for it.
+    pub(crate) fn deliver_due_with(
+        &self,
+        mut events: impl FnMut(&ScheduledToast) -> EventSender,
+    ) -> Result<Vec<ScheduleId>> {
+        let now = self.clock.now();
+        let due = {
+            let mut state = self.lock();
+            let count = state.toasts.partition_point(|toast| toast.due <= now);
+            if count == 0 {
+                return Ok(Vec::new());
+            }
+            let mut next = state.clone();
+            let due: Vec<_> = next.toasts.drain(..count).collect();
+            self.store.save(&next)?;
+            *state = next;
+            due
+        };
+
+        // The backend is called without the lock, so it can not block scheduling.
+        let mut delivered = Vec::new();
+        let mut due = due.into_iter();
+        for toast in due.by_ref() {
+            let shown = self
+                .notifier
+                .backend()
+                .show(&toast.app_id, &toast.payload, events(&toast));
+            if let Err(err) = shown {
+                self.restore(std::iter::once(toast).chain(due).collect());
+                return Err(err);
+            }
+            delivered.push(toast.id);
+        }
+        Ok(delivered)
+    }
+
+    /// Puts toasts that could not be shown back, keeping their ids and their place before the toasts due at the same time.
+    fn restore(&self, toasts: Vec<ScheduledToast>) {
+        let mut state = self.lock();
+        let mut next = state.clone();
+        for toast in toasts.into_iter().rev() {
+            let index = next
+                .toasts
+                .partition_point(|pending| pending.due < toast.due);
+            next.toasts.insert(index, toast);
+        }
+        // The error of the backend is the one returned, if the store fails the toasts
+        // are still pending in memory and saved with the next change.
+        let _ = self.store.save(&next);
+        *state = next;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ScheduleState> {
+        // The state is only replaced as a whole, a panic can not leave it half changed.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// Show the toast with `scheduler` at `when`.
+    pub fn schedule_at(&self, scheduler: &Scheduler, when: SystemTime) -> Result<ScheduleId> {
+        scheduler.schedule(self.payload()?, when)
+    }
+
+    /// Show the toast with `scheduler` once `delay` has passed on its clock.
+    pub fn schedule_in(&self, scheduler: &Scheduler, delay: Duration) -> Result<ScheduleId> {
+        self.schedule_at(scheduler, scheduler.now() + delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::backend::MemoryBackend;
+    use crate::error::{Error, Result};
+    use crate::notifier::Notifier;
+    use crate::schedule::{ManualClock, MemoryStore, ScheduleState, ScheduleStore, Scheduler};
+    use crate::Toast;
+
+    #[test]
+    fn delivers_when_due() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Calendar");
+        let clock = Arc::new(ManualClock::default());
+        let scheduler = Scheduler::new(&notifier, MemoryStore::new())
+            .unwrap()
+            .clock(clock.clone());
+        let mut toast = Toast::new().unwrap();
+        let later = toast
+            .schedule_in(&scheduler, Duration::from_secs(60))
+            .unwrap();
+        toast.title("Soon").unwrap();
+        let soon = toast
+            .schedule_in(&scheduler, Duration::from_secs(10))
+            .unwrap();
+        let cancelled = toast
+            .schedule_in(&scheduler, Duration::from_secs(20))
+            .unwrap();
+        assert!(scheduler.cancel(cancelled).unwrap());
+        assert!(!scheduler.cancel(cancelled).unwrap());
+
+        assert!(scheduler.deliver_due().unwrap().is_empty());
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(scheduler.deliver_due().unwrap(), [soon]);
+        assert!(backend.toasts()[0].payload.xml.contains("Soon"));
+        assert_eq!(
+            scheduler.next_due(),
+            Some(std::time::UNIX_EPOCH + Duration::from_secs(60))
+        );
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(scheduler.deliver_due().unwrap(), [later]);
+        assert!(scheduler.pending().is_empty());
+    }
+
+    #[test]
+    fn never_delivers_twice() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Calendar");
+        let clock = Arc::new(ManualClock::default());
+        let store = FlakyStore::default();
+        let failing = store.failing.clone();
+        let scheduler = Scheduler::new(&notifier, store)
+            .unwrap()
+            .clock(clock.clone());
+        let id = Toast::new()
+            .unwrap()
+            .schedule_in(&scheduler, Duration::from_secs(10))
+            .unwrap();
+
+        clock.advance(Duration::from_secs(10));
+        failing.store(true, Ordering::SeqCst);
+        assert!(scheduler.deliver_due().is_err());
+        assert!(backend.toasts().is_empty());
+        assert_eq!(scheduler.pending().len(), 1);
+
+        failing.store(false, Ordering::SeqCst);
+        assert_eq!(scheduler.deliver_due().unwrap(), [id]);
+        assert!(scheduler.deliver_due().unwrap().is_empty());
+        assert_eq!(backend.toasts().len(), 1);
+    }
+
+    /// A store whose saves fail while `failing` is set.
+    #[derive(Default)]
+    struct FlakyStore {
+        inner: MemoryStore,
+        failing: Arc<AtomicBool>,
+    }
+
+    impl ScheduleStore for FlakyStore {
+        fn load(&self) -> Result<ScheduleState> {
+            self.inner.load()
+        }
+
+        fn save(&self, state: &ScheduleState) -> Result<()> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(Error::Store {
+                    path: "flaky".into(),
+                    source: Arc::new(io::Error::other("disk full")),
+                });
+            }
+            self.inner.save(state)
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn survives_restarts() {
+        use crate::schedule::FileStore;
+
+        let path = std::env::temp_dir().join(format!("toasts-{}.json", std::process::id()));
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Calendar");
+        let clock = Arc::new(ManualClock::default());
+        let mut toast = Toast::new().unwrap();
+        toast.tag("standup").unwrap();
+        {
+            let scheduler = Scheduler::new(&notifier, FileStore::new(&path)).unwrap();
+            toast
+                .schedule_at(&scheduler, std::time::UNIX_EPOCH + Duration::from_secs(5))
+                .unwrap();
+            scheduler
+                .schedule_snoozed(
+                    toast.payload().unwrap(),
+                    std::time::UNIX_EPOCH + Duration::from_secs(10),
+                    2,
+                )
+                .unwrap();
+        }
+
+        let scheduler = Scheduler::new(&notifier, FileStore::new(&path))
+            .unwrap()
+            .clock(clock.clone());
+        let snoozes: Vec<u32> = scheduler.pending().iter().map(|t| t.snoozes).collect();
+        assert_eq!(snoozes, [0, 2]);
+        clock.advance(Duration::from_secs(10));
+        scheduler.deliver_due().unwrap();
+        assert_eq!(
+            backend.toasts()[0].payload.identity.get_tag(),
+            Some("standup")
+        );
+        assert!(Scheduler::new(&notifier, FileStore::new(&path))
+            .unwrap()
+            .pending()
+            .is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+}
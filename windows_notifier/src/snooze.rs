@@ -0,0 +1,389 @@
+//! Snoozing reminder and alarm toasts, showing them again after the interval the user picked.
+//!
+//! A snooze is an activation of an action with the arguments `snooze` that the app handles.
+//! The interval is read from the selection input of that action,
+//! whose selection ids are minutes like windows uses for its own snooze button.
+//!
+//! The snoozer re-delivers snoozed toasts with [`Snoozer::deliver_due`], keeping their events,
+//! so [`Snoozer::poll`] snoozes a re-delivered toast again when the user snoozes it.
+//! How often a toast was snoozed is kept in its [schedule entry](crate::schedule::ScheduledToast::snoozes),
+//! so with a `FileStore` the count survives restarts.
+//!
+//! The system snooze button, an action with [`SystemAction::Snooze`] or the [`AlarmCommands::Snooze`] command,
+//! is handled by windows, which shows the toast again by itself. Its activation never reaches the app,
+//! so the snoozer does not count it.
+//!
+//! ```
+//! # use std::collections::HashMap;
+//! # use std::sync::Arc;
+//! # use std::time::Duration;
+//! # use windows_notifier::{backend::MemoryBackend, notifier::Notifier, schedule::*, snooze::*, Toast};
+//! # use windows_notifier::tags::{action::Action, input::{Input, Selection}, toast::Reminder};
+//! let backend = Arc::new(MemoryBackend::new());
+//! let notifier = Notifier::with_backend(backend.clone(), "Contoso.Calendar");
+//! let clock = Arc::new(ManualClock::default());
+//! let scheduler = Arc::new(Scheduler::new(&notifier, MemoryStore::new())?.clock(clock.clone()));
+//! let snoozer = Snoozer::new(scheduler).max_snoozes(2);
+//!
+//! let mut toast = Toast::new()?.scenario(Reminder::new(Action::new("Snooze".into(), "snooze")))?;
+//! toast.add_input(Input::new_selection("snoozeTime", vec![Selection::new("10", "10 minutes")], Some("10")))?;
+//!
+//! // the user snoozed the toast the app showed
+//! let inputs = HashMap::from([("snoozeTime".to_string(), "10".to_string())]);
+//! let Snooze::Snoozed { interval, .. } = snoozer.handle(&toast, "snooze", &inputs)? else {
+//!     panic!("the toast has a snooze action");
+//! };
+//! assert_eq!(interval, Duration::from_secs(600));
+//!
+//! // the snoozer shows it again and handles the next snooze by itself
+//! clock.advance(interval);
+//! snoozer.deliver_due()?;
+//! backend.activate(0, "snooze", inputs);
+//! let events = snoozer.poll();
+//! assert!(matches!(events[0].snooze, Ok(Snooze::Snoozed { count: 2, .. })));
+//! # Ok::<(), windows_notifier::error::Error>(())
+//! ```
+//!
+//! [`SystemAction::Snooze`]: crate::tags::activation::SystemAction::Snooze
+//! [`AlarmCommands::Snooze`]: crate::tags::commands::AlarmCommands::Snooze
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use crate::backend::ToastPayload;
+use crate::error::{Error, Result};
+use crate::events::{DismissReason, EventSender, ToastEvent, ToastEvents};
+use crate::schedule::{ScheduleId, ScheduledToast, Scheduler};
+use crate::tags::activation::ActivationType;
+use crate::tags::input::Input;
+use crate::tags::toast::Scenarios;
+use crate::Toast;
+
+/// How often a toast can be snoozed by default.
+pub const DEFAULT_MAX_SNOOZES: u32 = 3;
+
+/// The arguments of the snooze action, the same windows uses for its system snooze button.
+pub const SNOOZE_ARGUMENTS: &str = "snooze";
+
+/// What the [`Snoozer`] did with an activation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Snooze {
+    /// The toast is shown again after `interval`, this was snooze number `count`.
+    Snoozed {
+        id: ScheduleId,
+        interval: Duration,
+        count: u32,
+    },
+    /// The toast was snoozed `count` times already and is not shown again.
+    LimitReached { count: u32 },
+    /// The activation was not a snooze of a reminder or alarm, the app handles it.
+    NotSnooze,
+}
+
+/// An event of a toast shown by [`Snoozer::deliver_due`], and what the snoozer did with it.
+#[derive(Debug)]
+pub struct SnoozerEvent {
+    /// The id the toast was scheduled with.
+    pub id: ScheduleId,
+    pub event: ToastEvent,
+    /// [`Snooze::NotSnooze`] for every event but a snooze.
+    pub snooze: Result<Snooze>,
+}
+
+/// A toast shown by [`Snoozer::deliver_due`] that can still send events.
+struct Delivered {
+    scheduled: ScheduledToast,
+    events: ToastEvents,
+}
+
+/// Handles the snooze activations of reminder and alarm toasts, re-delivering them with a [`Scheduler`].
+pub struct Snoozer {
+    scheduler: Arc<Scheduler>,
+    max_snoozes: u32,
+    default_interval: Duration,
+    delivered: Mutex<Vec<Delivered>>,
+}
+
+impl Snoozer {
+    pub fn new(scheduler: Arc<Scheduler>) -> Self {
+        Snoozer {
+            scheduler,
+            max_snoozes: DEFAULT_MAX_SNOOZES,
+            default_interval: Duration::from_secs(5 * 60),
+            delivered: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// How often a toast can be snoozed, [`DEFAULT_MAX_SNOOZES`] if not set.
+    pub fn max_snoozes(mut self, max_snoozes: u32) -> Self {
+        self.max_snoozes = max_snoozes;
+        self
+    }
+
+    /// The interval used when the snooze action has no selection input, 5 minutes if not set.
+    pub fn default_interval(mut self, default_interval: Duration) -> Self {
+        self.default_interval = default_interval;
+        self
+    }
+
+    /// Snooze `toast`, which the app showed itself, if `arguments` are its snooze action,
+    /// reading the interval from `user_inputs`.
+    ///
+    /// Returns [`Error::ActivationParse`] if the chosen selection is not a number of minutes.
+    pub fn handle<S, C>(
+        &self,
+        toast: &Toast<S, C>,
+        arguments: &str,
+        user_inputs: &HashMap<String, String>,
+    ) -> Result<Snooze> {
+        self.snooze(toast, 0, arguments, user_inputs, || toast.payload())
+    }
+
+    /// Show the due toasts of the scheduler, like [`Scheduler::deliver_due`], keeping their events for [`Snoozer::poll`].
+    pub fn deliver_due(&self) -> Result<Vec<ScheduleId>> {
+        let mut delivered = Vec::new();
+        let result = self.scheduler.deliver_due_with(|scheduled| {
+            let (sender, events) = EventSender::channel();
+            delivered.push(Delivered {
+                scheduled: scheduled.clone(),
+                events,
+            });
+            sender
+        });
+        self.lock().extend(delivered);
+        result
+    }
+
+    /// The events the toasts shown by [`Snoozer::deliver_due`] sent since the last poll,
+    /// snoozing the toasts again that the user snoozed.
+    ///
+    /// A toast is forgotten once it sends no more events, like after it was activated or closed.
+    pub fn poll(&self) -> Vec<SnoozerEvent> {
+        let mut events = Vec::new();
+        self.lock().retain(|delivered| loop {
+            let event = match delivered.events.try_next() {
+                Ok(Some(event)) => event,
+                Ok(None) => return true,
+                Err(_) => return false,
+            };
+            let snooze = match &event {
+                ToastEvent::Activated {
+                    arguments,
+                    user_inputs,
+                } => self.snooze_delivered(&delivered.scheduled, arguments, user_inputs),
+                _ => Ok(Snooze::NotSnooze),
+            };
+            // A toast that timed out is still in the Action Center, where it can be snoozed.
+            let ended = !matches!(
+                event,
+                ToastEvent::Dismissed {
+                    reason: DismissReason::TimedOut
+                }
+            );
+            events.push(SnoozerEvent {
+                id: delivered.scheduled.id,
+                event,
+                snooze,
+            });
+            if ended {
+                return false;
+            }
+        });
+        events
+    }
+
+    fn snooze_delivered(
+        &self,
+        scheduled: &ScheduledToast,
+        arguments: &str,
+        user_inputs: &HashMap<String, String>,
+    ) -> Result<Snooze> {
+        let toast = Toast::from_xml(&scheduled.payload.xml)?;
+        self.snooze(&toast, scheduled.snoozes, arguments, user_inputs, || {
+            Ok(scheduled.payload.clone())
+        })
+    }
+
+    /// Snooze `toast`, which was snoozed `snoozes` times before, showing `payload` again.
+    fn snooze<S, C>(
+        &self,
+        toast: &Toast<S, C>,
+        snoozes: u32,
+        arguments: &str,
+        user_inputs: &HashMap<String, String>,
+        payload: impl FnOnce() -> Result<ToastPayload>,
+    ) -> Result<Snooze> {
+        if !matches!(toast.scenario, Some(Scenarios::Reminder | Scenarios::Alarm)) {
+            return Ok(Snooze::NotSnooze);
+        }
+        let Some(input_id) = toast.snooze_input(arguments) else {
+            return Ok(Snooze::NotSnooze);
+        };
+        let now = self.scheduler.now();
+        let interval = match input_id.and_then(|id| user_inputs.get(id)) {
+            Some(minutes) => parse_minutes(minutes, now)?,
+            None => self.default_interval,
+        };
+        if snoozes >= self.max_snoozes {
+            return Ok(Snooze::LimitReached { count: snoozes });
+        }
+        let count = snoozes + 1;
+        let id = self
+            .scheduler
+            .schedule_snoozed(payload()?, now + interval, count)?;
+        Ok(Snooze::Snoozed {
+            id,
+            interval,
+            count,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Delivered>> {
+        // The list is only changed by retain and extend, a panic can not leave it half changed.
+        self.delivered
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// If `arguments` are the snooze action of this toast, the id of the input the interval is picked in.
+    /// System snoozes are handled by windows and never reach the app, so they are not matched.
+    fn snooze_input(&self, arguments: &str) -> Option<Option<&str>> {
+        if arguments != SNOOZE_ARGUMENTS {
+            return None;
+        }
+        let selection = self.actions.inputs.iter().find_map(|input| match input {
+            Input::Selection { id, .. } => Some(id.as_str()),
+            Input::Text { .. } => None,
+        });
+        self.actions
+            .actions
+            .iter()
+            .find(|action| {
+                action.arguments == SNOOZE_ARGUMENTS
+                    && action.activation_type != Some(ActivationType::System)
+            })
+            .map(|action| action.hint_input_id.as_deref().or(selection))
+    }
+}
+
+/// The interval of a snooze selection, which comes from the toast xml and can be anything,
+/// so intervals that can not be added to `now` are rejected too.
+fn parse_minutes(minutes: &str, now: SystemTime) -> Result<Duration> {
+    minutes
+        .parse::<u64>()
+        .ok()
+        .and_then(|minutes| minutes.checked_mul(60))
+        .map(Duration::from_secs)
+        .filter(|interval| now.checked_add(*interval).is_some())
+        .ok_or_else(|| Error::ActivationParse {
+            input: minutes.into(),
+            message: "the snooze interval is not a number of minutes".into(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::backend::MemoryBackend;
+    use crate::error::Error;
+    use crate::events::ToastEvent;
+    use crate::notifier::Notifier;
+    use crate::schedule::{ManualClock, MemoryStore, Scheduler};
+    use crate::snooze::{Snooze, Snoozer};
+    use crate::tags::action::Action;
+    use crate::tags::activation::SystemAction;
+    use crate::tags::commands::AlarmCommands;
+    use crate::tags::input::{Input, Selection};
+    use crate::tags::toast::Alarm;
+    use crate::Toast;
+
+    #[test]
+    fn redelivers_until_limit() {
+        let backend = Arc::new(MemoryBackend::new());
+        let notifier = Notifier::with_backend(backend.clone(), "Contoso.Calendar");
+        let clock = Arc::new(ManualClock::default());
+        let scheduler = Arc::new(
+            Scheduler::new(&notifier, MemoryStore::new())
+                .unwrap()
+                .clock(clock.clone()),
+        );
+        let snoozer = Snoozer::new(scheduler.clone()).max_snoozes(2);
+
+        let mut toast = Toast::new().unwrap().scenario(Alarm).unwrap();
+        toast
+            .add_input(Input::new_selection(
+                "snoozeTime",
+                vec![
+                    Selection::new("1", "1 minute"),
+                    Selection::new("15", "15 minutes"),
+                ],
+                Some("15"),
+            ))
+            .unwrap();
+        toast
+            .add_action(Action::new("Snooze".into(), "snooze").hint_input_id("snoozeTime".into()))
+            .unwrap();
+        toast
+            .add_command(AlarmCommands::Snooze, "action=snooze")
+            .unwrap();
+        let inputs = HashMap::from([("snoozeTime".to_string(), "1".to_string())]);
+
+        // the system snooze is handled by windows and never counted
+        assert_eq!(
+            snoozer.handle(&toast, "action=snooze", &inputs).unwrap(),
+            Snooze::NotSnooze
+        );
+        let snooze = snoozer.handle(&toast, "snooze", &inputs).unwrap();
+        assert!(matches!(snooze, Snooze::Snoozed { count: 1, .. }));
+
+        for expected in [Some(2), None] {
+            clock.advance(Duration::from_secs(60));
+            assert_eq!(snoozer.deliver_due().unwrap().len(), 1);
+            assert!(backend.activate(0, "snooze", inputs.clone()));
+            let events = snoozer.poll();
+            assert_eq!(events.len(), 1);
+            assert!(matches!(events[0].event, ToastEvent::Activated { .. }));
+            match (events[0].snooze.as_ref().unwrap(), expected) {
+                (Snooze::Snoozed { count, .. }, Some(expected)) => {
+                    assert_eq!(*count, expected);
+                    assert_eq!(scheduler.pending()[0].snoozes, expected);
+                }
+                (snooze, None) => assert_eq!(*snooze, Snooze::LimitReached { count: 2 }),
+                (snooze, _) => panic!("unexpected {snooze:?}"),
+            }
+        }
+        assert!(scheduler.pending().is_empty());
+        assert!(snoozer.poll().is_empty());
+
+        for minutes in [
+            "soon".to_string(),
+            u64::MAX.to_string(),
+            (u64::MAX / 60).to_string(),
+        ] {
+            let inputs = HashMap::from([("snoozeTime".to_string(), minutes)]);
+            assert!(matches!(
+                snoozer.handle(&toast, "snooze", &inputs),
+                Err(Error::ActivationParse { .. })
+            ));
+        }
+
+        let mut system = Toast::new().unwrap().scenario(Alarm).unwrap();
+        system
+            .add_action(Action::system("Snooze".into(), SystemAction::Snooze))
+            .unwrap();
+        assert_eq!(
+            snoozer.handle(&system, "snooze", &inputs).unwrap(),
+            Snooze::NotSnooze
+        );
+
+        // the snoozer can be kept by the thread that handles activations
+        let events = std::thread::spawn(move || snoozer.poll());
+        assert!(events.join().unwrap().is_empty());
+    }
+}
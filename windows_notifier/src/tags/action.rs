@@ -1,89 +1,151 @@
-use crate::error::Result;
-use crate::Toast;
+use std::fmt;
+use std::io::Write;
 
-/// Decides the type of activation that will be used when the user interacts with a specific action.
-#[derive(Debug, Clone, Copy, Default)]
-pub enum ActivationType {
-    /// Default value. Your foreground app is launched.
-    #[default]
-    Forground,
-    ///  Your corresponding background task is triggered, and you can execute code in the background without interrupting the user.
-    Background,
-    /// Launch a different app using protocol activation.
-    Protocol,
-}
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
 
-impl ActivationType {
-    fn to_string(&self) -> String {
-        match self {
-            Self::Forground => "foreground".to_string(),
-            Self::Background => "background".to_string(),
-            Self::Protocol => "protocol".to_string(),
-        }
-    }
-}
+use crate::error::{Error, Result};
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_empty, ToXML};
+use crate::Toast;
+
+use super::activation::{protocol_uri, ActivationType, AfterActivationBehavior, SystemAction};
+use super::toast::Scenarios;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum ButtonStyle {
     Success,
     Critical,
 }
 
 impl ButtonStyle {
-    fn to_string(&self) -> String {
+    fn from_attr(value: &str) -> Option<ButtonStyle> {
+        match value {
+            "Success" => Some(Self::Success),
+            "Critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ButtonStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Success => "Success".to_string(),
-            Self::Critical => "Critical".to_string(),
+            Self::Success => write!(f, "Success"),
+            Self::Critical => write!(f, "Critical"),
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct Action {
     ///  The content displayed on the button.
-    content: String,
+    pub(crate) content: String,
+    /// The message id the content is [localized](crate::localize) from, not part of the xml.
+    pub(crate) content_message: Option<String>,
     /// App-defined string of arguments that the app will later receive if the user clicks this button.
-    arguments: String,
+    pub(crate) arguments: String,
     /// Decides the type of activation that will be used when the user interacts with a specific action.
-    activation_type: Option<ActivationType>,
+    pub(crate) activation_type: Option<ActivationType>,
+    /// The package family name of the app that handles a protocol activation.
+    pub(crate) target_application_pfn: Option<String>,
+    /// What the toast does after this button is clicked.
+    pub(crate) after_activation_behavior: Option<AfterActivationBehavior>,
     /// When set to true, the action becomes a context menu action added to the toast notification's context menu rather than a traditional toast button.
-    context_menu: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) context_menu: bool,
     /// The URI of the image source for a toast button icon. These icons are white transparent 16x16 pixel images at 100% scaling and should have no padding included in the image itself. If you choose to provide icons on a toast notification, you must provide icons for ALL of your buttons in the notification, as it transforms the style of your buttons into icon buttons. Use one of the following protocol handlers:
     ///
     /// - http:// or https:// - A web-based image.
     /// - ms-appx:/// - An image included in the app package.
     /// - ms-appdata:///local/ - An image saved to local storage.
     /// - file:/// - A local image. (Supported only for desktop apps. This protocol cannot be used by UWP apps.)
-    image_uri: Option<String>,
+    pub(crate) image_uri: Option<String>,
     /// Set to the Id of an input to position button beside the input.
-    hint_input_id: Option<String>,
+    pub(crate) hint_input_id: Option<String>,
     /// The button style. useButtonStyle must be set to true in the toast element.
     ///
     /// - "Success" - The button is green
     /// - "Critical" - The button is red.
-    hint_button_style: Option<ButtonStyle>,
+    pub(crate) hint_button_style: Option<ButtonStyle>,
     /// The tooltip for a button, if the button has an empty content string.
-    hint_tooltip: Option<String>,
+    pub(crate) hint_tooltip: Option<String>,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
 }
 
 impl Action {
-    pub fn new(content: String, arguments: String) -> Self {
+    /// `arguments` can be a plain string or [`ToastArguments`](crate::arguments::ToastArguments).
+    pub fn new(content: String, arguments: impl Into<String>) -> Self {
         Self {
             content,
-            arguments,
+            content_message: None,
+            arguments: arguments.into(),
             activation_type: None,
+            target_application_pfn: None,
+            after_activation_behavior: None,
             context_menu: false,
             image_uri: None,
             hint_input_id: None,
             hint_button_style: None,
             hint_tooltip: None,
+            extra: Extra::default(),
         }
     }
 
+    /// A button launching `uri` with protocol activation.
+    ///
+    /// Returns [`Error::InvalidArgument`] if `uri` is not an absolute URI.
+    pub fn protocol(content: String, uri: &str) -> Result<Self> {
+        let uri = protocol_uri("toast/actions/action", uri)?;
+        Ok(Action::new(content, uri).activation_type(ActivationType::Protocol))
+    }
+
+    /// A button windows handles itself, with the system text if `content` is empty.
+    ///
+    /// Point a snooze button at a selection input of minutes with [`Action::hint_input_id`] to let the user pick the interval.
+    pub fn system(content: String, action: SystemAction) -> Self {
+        Action::new(content, action.arguments()).activation_type(ActivationType::System)
+    }
+
+    /// Replace the content with message `id` when the toast is [localized](crate::Toast::localize).
+    pub fn content_message(mut self, id: impl Into<String>) -> Self {
+        self.content_message = Some(id.into());
+        self
+    }
+
     pub fn activation_type(mut self, activation_type: ActivationType) -> Self {
         self.activation_type = Some(activation_type);
         self
     }
 
+    /// The package family name of the app that handles the protocol activation of this button.
+    pub fn target_application_pfn(mut self, pfn: impl Into<String>) -> Self {
+        self.target_application_pfn = Some(pfn.into());
+        self
+    }
+
+    /// What the toast does after this button is clicked,
+    /// [`AfterActivationBehavior::PendingUpdate`] keeps it on screen for a [multi-step interaction](crate::interaction).
+    pub fn after_activation_behavior(mut self, behavior: AfterActivationBehavior) -> Self {
+        self.after_activation_behavior = Some(behavior);
+        self
+    }
+
     pub fn context_menu(mut self) -> Self {
         self.context_menu = true;
         self
@@ -108,49 +170,289 @@ impl Action {
         self.hint_tooltip = Some(hint_tooltip);
         self
     }
+
+    /// The content displayed on the button.
+    pub fn get_content(&self) -> &str {
+        &self.content
+    }
+
+    /// The message id the content is localized from.
+    pub fn get_content_message(&self) -> Option<&str> {
+        self.content_message.as_deref()
+    }
+
+    /// The arguments the app receives if the user clicks this button.
+    pub fn get_arguments(&self) -> &str {
+        &self.arguments
+    }
+
+    /// The type of activation used when the user clicks this button.
+    pub fn get_activation_type(&self) -> Option<ActivationType> {
+        self.activation_type
+    }
+
+    /// The package family name of the app that handles the protocol activation.
+    pub fn get_target_application_pfn(&self) -> Option<&str> {
+        self.target_application_pfn.as_deref()
+    }
+
+    /// What the toast does after this button is clicked.
+    pub fn get_after_activation_behavior(&self) -> Option<AfterActivationBehavior> {
+        self.after_activation_behavior
+    }
+
+    /// The system action of a button with [`ActivationType::System`].
+    pub fn get_system_action(&self) -> Option<SystemAction> {
+        (self.activation_type == Some(ActivationType::System))
+            .then(|| SystemAction::from_arguments(&self.arguments))
+            .flatten()
+    }
+
+    /// Whether the action is a context menu entry instead of a button.
+    pub fn is_context_menu(&self) -> bool {
+        self.context_menu
+    }
+
+    /// The URI of the button icon.
+    pub fn get_image_uri(&self) -> Option<&str> {
+        self.image_uri.as_deref()
+    }
+
+    /// The id of the input this button is positioned beside.
+    pub fn get_hint_input_id(&self) -> Option<&str> {
+        self.hint_input_id.as_deref()
+    }
+
+    /// The button style.
+    pub fn get_hint_button_style(&self) -> Option<ButtonStyle> {
+        self.hint_button_style
+    }
+
+    /// The tooltip of the button.
+    pub fn get_hint_tooltip(&self) -> Option<&str> {
+        self.hint_tooltip.as_deref()
+    }
+
+    /// Anything that was not understood when parsing.
+    pub fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
-impl<S> Toast<S> {
-    /// add custom action to the toast notification.
-    pub fn add_action(&self, action: Action) -> Result<()> {
-        let action_node = self.doc.CreateElement(&"action".into()).unwrap();
-        action_node.SetAttribute(&"content".into(), &action.content.into())?;
-        action_node.SetAttribute(&"arguments".into(), &action.arguments.into())?;
+impl ToXML for Action {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut action_node = BytesStart::new("action");
+        action_node.push_attribute(("content", self.content.as_str()));
+        action_node.push_attribute(("arguments", self.arguments.as_str()));
+
+        if let Some(activation_type) = self.activation_type {
+            action_node.push_attribute(("activationType", activation_type.to_string().as_str()));
+        }
+
+        if let Some(pfn) = &self.target_application_pfn {
+            action_node.push_attribute(("protocolActivationTargetApplicationPfn", pfn.as_str()));
+        }
+
+        if let Some(behavior) = self.after_activation_behavior {
+            action_node.push_attribute(("afterActivationBehavior", behavior.to_string().as_str()));
+        }
+
+        if self.context_menu {
+            action_node.push_attribute(("placement", "contextMenu"));
+        }
 
-        if let Some(activation_type) = action.activation_type {
-            action_node.SetAttribute(
-                &"activationType".into(),
-                &activation_type.to_string().into(),
-            )?;
+        if let Some(image_uri) = &self.image_uri {
+            action_node.push_attribute(("imageUri", image_uri.as_str()));
         }
 
-        if action.context_menu {
-            action_node.SetAttribute(&"placement".into(), &"contextMenu".into())?;
+        if let Some(hint_input_id) = &self.hint_input_id {
+            action_node.push_attribute(("hint-inputId", hint_input_id.as_str()));
         }
 
-        if let Some(image_uri) = action.image_uri {
-            action_node.SetAttribute(&"imageUri".into(), &image_uri.into())?;
+        if let Some(hint_button_style) = self.hint_button_style {
+            action_node
+                .push_attribute(("hint-buttonStyle", hint_button_style.to_string().as_str()));
         }
 
-        if let Some(hint_input_id) = action.hint_input_id {
-            action_node.SetAttribute(&"hint-inputId".into(), &hint_input_id.into())?;
+        if let Some(hint_tooltip) = &self.hint_tooltip {
+            action_node.push_attribute(("hint-tooltip", hint_tooltip.as_str()));
         }
 
-        if let Some(hint_button_style) = action.hint_button_style {
-            action_node.SetAttribute(
-                &"hint-buttonStyle".into(),
-                &hint_button_style.to_string().into(),
-            )?;
+        self.extra.push_attributes(&mut action_node);
+        write_empty(writer, action_node, &self.extra)
+    }
+}
+
+impl FromXML for Action {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
         }
+        let mut attributes = Attributes::new(element);
+        let content = attributes.take("content")?;
+        let arguments = attributes.take("arguments")?;
+        let activation_type = attributes.take_with("activationType", ActivationType::from_attr);
+        let target_application_pfn = attributes.take("protocolActivationTargetApplicationPfn");
+        let after_activation_behavior = attributes.take_with(
+            "afterActivationBehavior",
+            AfterActivationBehavior::from_attr,
+        );
+        let context_menu = attributes
+            .take_with("placement", |placement| {
+                (placement == "contextMenu").then_some(true)
+            })
+            .unwrap_or(false);
+        let image_uri = attributes.take("imageUri");
+        let hint_input_id = attributes.take("hint-inputId");
+        let hint_button_style = attributes.take_with("hint-buttonStyle", ButtonStyle::from_attr);
+        let hint_tooltip = attributes.take("hint-tooltip");
+        Some(Action {
+            content,
+            content_message: None,
+            arguments,
+            activation_type,
+            target_application_pfn,
+            after_activation_behavior,
+            context_menu,
+            image_uri,
+            hint_input_id,
+            hint_button_style,
+            hint_tooltip,
+            extra: attributes.into_extra(element.elements().cloned().collect()),
+        })
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// The buttons and context menu entries of the toast.
+    pub fn actions(&self) -> &[Action] {
+        &self.actions.actions
+    }
 
-        if let Some(hint_tooltip) = action.hint_tooltip {
-            action_node.SetAttribute(&"hint-tooltip".into(), &hint_tooltip.into())?;
+    /// Change the action at `index` in place with `edit`.
+    ///
+    /// Returns [`Error::InvalidArgument`] and keeps the action as it was if there is no action at `index`,
+    /// or if the edit takes away the background action a [`Reminder`](super::toast::Reminder) needs.
+    pub fn edit_action(&mut self, index: usize, edit: impl FnOnce(&mut Action)) -> Result<()> {
+        let path = action_path(index);
+        let Some(action) = self.actions.actions.get(index) else {
+            return Err(Error::invalid_argument(path, "there is no such action"));
+        };
+        let mut edited = action.clone();
+        edit(&mut edited);
+        let previous = std::mem::replace(&mut self.actions.actions[index], edited);
+        if let Err(err) = self.check_reminder(path) {
+            self.actions.actions[index] = previous;
+            return Err(err);
         }
+        Ok(())
+    }
 
-        self.doc
-            .SelectSingleNode(&"/toast/actions".into())?
-            .AppendChild(&action_node)?;
+    /// Remove the action at `index`, returning it.
+    ///
+    /// Returns [`Error::InvalidArgument`] if there is no action at `index`,
+    /// or if it is the background action a [`Reminder`](super::toast::Reminder) needs.
+    pub fn remove_action(&mut self, index: usize) -> Result<Action> {
+        let path = action_path(index);
+        if index >= self.actions.actions.len() {
+            return Err(Error::invalid_argument(path, "there is no such action"));
+        }
+        let action = self.actions.actions.remove(index);
+        if let Err(err) = self.check_reminder(path) {
+            self.actions.actions.insert(index, action);
+            return Err(err);
+        }
+        Ok(action)
+    }
 
+    /// Fails with [`Error::InvalidArgument`] at `path` if the toast is a reminder windows would not show as one.
+    pub(crate) fn check_reminder(&self, path: String) -> Result<()> {
+        if self.scenario == Some(Scenarios::Reminder) && !self.actions.keeps_reminder() {
+            return Err(Error::invalid_argument(
+                path,
+                "a reminder needs a background or system action",
+            ));
+        }
         Ok(())
     }
 }
+
+/// The path of the action at `index`, counted from 1 like the paths of [`Toast::validate`].
+fn action_path(index: usize) -> String {
+    format!("toast/actions/action[{}]", index + 1)
+}
+
+impl<S, C> Toast<S, C> {
+    /// add custom action to the toast notification.
+    /// A toast can have at most [`MAX_ACTIONS`](crate::validate::MAX_ACTIONS) actions.
+    pub fn add_action(&mut self, action: Action) -> Result<()> {
+        self.actions.push_action(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::tags::action::Action;
+    use crate::tags::activation::ActivationType;
+    use crate::tags::toast::Reminder;
+    use crate::validate::MAX_ACTIONS;
+    use crate::Toast;
+
+    #[test]
+    fn keeps_reminder_action() {
+        let mut toast = Toast::new()
+            .unwrap()
+            .scenario(Reminder::new(Action::new("Done".into(), "done")))
+            .unwrap();
+        toast
+            .add_action(Action::new("Open".into(), "open"))
+            .unwrap();
+
+        assert!(toast
+            .edit_action(0, |action| {
+                action.activation_type = Some(ActivationType::Foreground)
+            })
+            .is_err());
+        assert!(toast.remove_action(0).is_err());
+        assert!(toast.remove_action(2).is_err());
+        assert_eq!(
+            toast.actions()[0].get_activation_type(),
+            Some(ActivationType::Background)
+        );
+
+        toast
+            .edit_action(1, |action| action.content = "Open app".into())
+            .unwrap();
+        assert_eq!(toast.remove_action(1).unwrap().get_content(), "Open app");
+        assert_eq!(toast.actions().len(), 1);
+        assert!(toast.validate().is_empty());
+
+        // the snooze and dismiss buttons keep the reminder once the background action is gone
+        toast.snooze_and_dismiss(true).unwrap();
+        toast.remove_action(0).unwrap();
+        assert!(toast.snooze_and_dismiss(false).is_err());
+        assert!(toast.is_snooze_and_dismiss());
+        assert!(toast.validate().is_empty());
+    }
+
+    #[test]
+    fn limits_reminder_actions() {
+        let mut toast = Toast::new().unwrap();
+        for i in 0..MAX_ACTIONS {
+            toast
+                .add_action(Action::new(i.to_string(), "open"))
+                .unwrap();
+        }
+        let Err(err) = toast.scenario(Reminder::new(Action::new("Done".into(), "done"))) else {
+            panic!("the reminder action is over the limit");
+        };
+        assert!(matches!(
+            err,
+            Error::LimitExceeded {
+                limit: MAX_ACTIONS,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::Write;
+
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
+
+use crate::error::{Error, Result};
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_children, ToXML};
+use crate::validate::MAX_ACTIONS;
+use crate::Toast;
+
+use super::action::Action;
+use super::activation::ActivationType;
+use super::input::Input;
+
+/// The `actions` element of the toast, holding the inputs and buttons.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
+pub struct Actions {
+    /// The inputs, always written before the actions.
+    pub(crate) inputs: Vec<Input>,
+    /// The buttons and context menu entries.
+    pub(crate) actions: Vec<Action>,
+    /// Whether windows adds its own snooze and dismiss buttons, `hint-systemCommands="SnoozeAndDismiss"`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "std::ops::Not::not"))]
+    pub(crate) snooze_and_dismiss: bool,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
+}
+
+impl Actions {
+    pub(crate) fn new() -> Actions {
+        Actions::default()
+    }
+
+    /// Whether a reminder with these actions is shown as one, which needs a background or system action
+    /// or the snooze and dismiss buttons of windows.
+    pub(crate) fn keeps_reminder(&self) -> bool {
+        self.snooze_and_dismiss
+            || self.actions.iter().any(|action| {
+                matches!(
+                    action.activation_type,
+                    Some(ActivationType::Background | ActivationType::System)
+                )
+            })
+    }
+
+    /// Adds a button, at most [`MAX_ACTIONS`] of them.
+    pub(crate) fn push_action(&mut self, action: Action) -> Result<()> {
+        if self.actions.len() >= MAX_ACTIONS {
+            return Err(Error::limit_exceeded(
+                format!("toast/actions/action[{}]", self.actions.len() + 1),
+                MAX_ACTIONS,
+            ));
+        }
+        self.actions.push(action);
+        Ok(())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+            && self.actions.is_empty()
+            && !self.snooze_and_dismiss
+            && self.extra.is_empty()
+    }
+}
+
+impl ToXML for Actions {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut actions = BytesStart::new("actions");
+        if self.snooze_and_dismiss {
+            actions.push_attribute(("hint-systemCommands", "SnoozeAndDismiss"));
+        }
+        self.extra.push_attributes(&mut actions);
+        write_children(writer, actions, |writer| {
+            for input in &self.inputs {
+                input.write_xml(writer)?;
+            }
+            for action in &self.actions {
+                action.write_xml(writer)?;
+            }
+            self.extra.write_elements(writer)
+        })
+    }
+}
+
+impl FromXML for Actions {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
+        }
+        let mut actions = Actions::new();
+        let mut unknown = Vec::new();
+        for child in element.elements() {
+            match child.name.as_str() {
+                "input" => match Input::from_raw(child) {
+                    Some(input) => actions.inputs.push(input),
+                    None => unknown.push(child.clone()),
+                },
+                "action" => match Action::from_raw(child) {
+                    Some(action) => actions.actions.push(action),
+                    None => unknown.push(child.clone()),
+                },
+                _ => unknown.push(child.clone()),
+            }
+        }
+        let mut attributes = Attributes::new(element);
+        actions.snooze_and_dismiss = attributes
+            .take_with("hint-systemCommands", |commands| {
+                (commands == "SnoozeAndDismiss").then_some(true)
+            })
+            .unwrap_or(false);
+        actions.extra = attributes.into_extra(unknown);
+        Some(actions)
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// Whether windows adds its own snooze and dismiss buttons.
+    pub fn is_snooze_and_dismiss(&self) -> bool {
+        self.actions.snooze_and_dismiss
+    }
+
+    /// Let windows add its own snooze and dismiss buttons, with a selection of snooze intervals.
+    /// The toast can still have context menu entries, but no other buttons or inputs.
+    ///
+    /// Returns [`Error::InvalidArgument`] and keeps the buttons
+    /// if turning them off leaves a [`Reminder`](super::toast::Reminder) without a background action.
+    pub fn snooze_and_dismiss(&mut self, snooze_and_dismiss: bool) -> Result<()> {
+        let previous = std::mem::replace(&mut self.actions.snooze_and_dismiss, snooze_and_dismiss);
+        if let Err(err) = self.check_reminder("toast/actions/@hint-systemCommands".into()) {
+            self.actions.snooze_and_dismiss = previous;
+            return Err(err);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! How the app is activated when the user clicks the toast, one of its actions, its header or a command.
+//!
+//! The toast, [`Action`](super::action::Action) and [`Header`](super::header::Header) all take an [`ActivationType`],
+//! with protocol activation launching the URI in their arguments,
+//! optionally in the app with the package family name set as the target application.
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+
+/// The type of activation used when the user clicks the toast or one of its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum ActivationType {
+    /// Default value. Your foreground app is launched.
+    #[default]
+    Foreground,
+    /// Your corresponding background task is triggered, and you can execute code in the background without interrupting the user.
+    /// Not supported by the header.
+    Background,
+    /// Launch a different app using protocol activation, the arguments are the URI to launch.
+    Protocol,
+    /// A [`SystemAction`] handled by windows, only supported by actions.
+    System,
+}
+
+impl ActivationType {
+    pub(crate) fn from_attr(value: &str) -> Option<ActivationType> {
+        match value {
+            "foreground" => Some(Self::Foreground),
+            "background" => Some(Self::Background),
+            "protocol" => Some(Self::Protocol),
+            "system" => Some(Self::System),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ActivationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Foreground => write!(f, "foreground"),
+            Self::Background => write!(f, "background"),
+            Self::Protocol => write!(f, "protocol"),
+            Self::System => write!(f, "system"),
+        }
+    }
+}
+
+/// What the toast does after the user clicks a button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum AfterActivationBehavior {
+    /// Default value. The toast is dismissed.
+    #[default]
+    Default,
+    /// The toast stays on screen in a pending state until the app replaces it,
+    /// for background actions that show their result in the toast.
+    PendingUpdate,
+}
+
+impl AfterActivationBehavior {
+    pub(crate) fn from_attr(value: &str) -> Option<AfterActivationBehavior> {
+        match value {
+            "default" => Some(Self::Default),
+            "pendingUpdate" => Some(Self::PendingUpdate),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AfterActivationBehavior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::PendingUpdate => write!(f, "pendingUpdate"),
+        }
+    }
+}
+
+/// An action windows handles itself, without activating the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum SystemAction {
+    /// Show the toast again later, after the interval picked in the input of `hint-inputId`.
+    Snooze,
+    /// Dismiss the toast.
+    Dismiss,
+}
+
+impl SystemAction {
+    /// The arguments of an action with [`ActivationType::System`].
+    pub fn arguments(&self) -> &'static str {
+        match self {
+            SystemAction::Snooze => "snooze",
+            SystemAction::Dismiss => "dismiss",
+        }
+    }
+
+    pub(crate) fn from_arguments(arguments: &str) -> Option<SystemAction> {
+        match arguments {
+            "snooze" => Some(SystemAction::Snooze),
+            "dismiss" => Some(SystemAction::Dismiss),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `uri` is an absolute URI, a scheme followed by `:` and the rest without whitespace.
+pub(crate) fn is_uri(uri: &str) -> bool {
+    let Some((scheme, rest)) = uri.split_once(':') else {
+        return false;
+    };
+    let mut scheme = scheme.chars();
+    scheme.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !uri.chars().any(char::is_whitespace)
+}
+
+/// The URI of a protocol activation, or [`Error::InvalidArgument`] for `path` if it is not one.
+pub(crate) fn protocol_uri(path: &str, uri: &str) -> Result<String> {
+    if !is_uri(uri) {
+        return Err(Error::invalid_argument(
+            path,
+            format!("{:?} is not an absolute URI for protocol activation", uri),
+        ));
+    }
+    Ok(uri.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::action::Action;
+    use crate::tags::activation::{ActivationType, SystemAction};
+    use crate::tags::header::Header;
+    use crate::Toast;
+
+    #[test]
+    fn writes_and_checks_activation() {
+        assert!(Action::protocol("Open".into(), "not a uri").is_err());
+        assert!(Header::protocol("6289", "Camping!!", "example.com/camping").is_err());
+
+        let mut toast = Toast::new().unwrap();
+        toast
+            .protocol_launch("https://example.com/camping")
+            .unwrap();
+        toast
+            .target_application_pfn("Microsoft.WindowsStore_8wekyb3d8bbwe")
+            .unwrap();
+        assert!(toast.activation_type(ActivationType::System).is_err());
+        toast.snooze_and_dismiss(true).unwrap();
+        toast
+            .add_action(Action::system("".into(), SystemAction::Snooze).context_menu())
+            .unwrap();
+        toast
+            .add_header(
+                Header::new("6289", "Camping!!", "camping")
+                    .activation_type(ActivationType::Background),
+            )
+            .unwrap();
+
+        let xml = toast.into_raw().unwrap();
+        assert!(xml.starts_with(
+            r#"<toast launch="https://example.com/camping" activationType="protocol" protocolActivationTargetApplicationPfn="Microsoft.WindowsStore_8wekyb3d8bbwe">"#
+        ));
+        assert!(xml.contains(r#"<actions hint-systemCommands="SnoozeAndDismiss"><action content="" arguments="snooze" activationType="system" placement="contextMenu"/>"#));
+        let parsed = Toast::from_xml(&xml).unwrap();
+        assert_eq!(parsed.into_raw().unwrap(), xml);
+        assert_eq!(
+            parsed.actions()[0].get_system_action(),
+            Some(SystemAction::Snooze)
+        );
+
+        let paths: Vec<_> = parsed.validate().into_iter().map(|d| d.path).collect();
+        assert_eq!(paths, ["toast/header"]);
+
+        toast
+            .add_action(Action::new("Reply".into(), "reply"))
+            .unwrap();
+        toast
+            .edit_action(0, |action| {
+                action.activation_type = Some(ActivationType::Protocol)
+            })
+            .unwrap();
+        let paths: Vec<_> = toast.validate().into_iter().map(|d| d.path).collect();
+        assert_eq!(
+            paths,
+            ["toast/actions/action[1]", "toast/header", "toast/actions"]
+        );
+    }
+}
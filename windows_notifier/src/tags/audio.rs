@@ -1,12 +1,19 @@
+use std::fmt;
+use std::io::Write;
 use std::marker::PhantomData;
 
-use windows::Data::Xml::Dom::XmlDocument;
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
 
 use crate::error::Result;
-use crate::utils::into_raw::ToXML;
-use crate::Toast;
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_empty, ToXML};
+use crate::{HasAudio, Toast};
 
-#[derive(Debug, Clone, Copy)]
+use super::toast::{Duration, LoopingScenario};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Notification {
     /// ms-winsoundevent:Notification.Default
     Default,
@@ -61,48 +68,110 @@ pub enum Notification {
 }
 
 impl Notification {
-    fn to_string(&self) -> String {
-        match self {
-            Notification::Default => "ms-winsoundevent:Notification.Default".into(),
-            Notification::IM => "ms-winsoundevent:Notification.IM".into(),
-            Notification::Mail => "ms-winsoundevent:Notification.Mail".into(),
-            Notification::Reminder => "ms-winsoundevent:Notification.Reminder".into(),
-            Notification::SMS => "ms-winsoundevent:Notification.SMS".into(),
-            Notification::LoopingAlarm => "ms-winsoundevent:Notification.Looping.Alarm".into(),
-            Notification::LoopingAlarm2 => "ms-winsoundevent:Notification.Looping.Alarm2".into(),
-            Notification::LoopingAlarm3 => "ms-winsoundevent:Notification.Looping.Alarm3".into(),
-            Notification::LoopingAlarm4 => "ms-winsoundevent:Notification.Looping.Alarm4".into(),
-            Notification::LoopingAlarm5 => "ms-winsoundevent:Notification.Looping.Alarm5".into(),
-            Notification::LoopingAlarm6 => "ms-winsoundevent:Notification.Looping.Alarm6".into(),
-            Notification::LoopingAlarm7 => "ms-winsoundevent:Notification.Looping.Alarm7".into(),
-            Notification::LoopingAlarm8 => "ms-winsoundevent:Notification.Looping.Alarm8".into(),
-            Notification::LoopingAlarm9 => "ms-winsoundevent:Notification.Looping.Alarm9".into(),
-            Notification::LoopingAlarm10 => "ms-winsoundevent:Notification.Looping.Alarm10".into(),
-            Notification::LoopingCall => "ms-winsoundevent:Notification.Looping.Call".into(),
-            Notification::LoopingCall2 => "ms-winsoundevent:Notification.Looping.Call2".into(),
-            Notification::LoopingCall3 => "ms-winsoundevent:Notification.Looping.Call3".into(),
-            Notification::LoopingCall4 => "ms-winsoundevent:Notification.Looping.Call4".into(),
-            Notification::LoopingCall5 => "ms-winsoundevent:Notification.Looping.Call5".into(),
-            Notification::LoopingCall6 => "ms-winsoundevent:Notification.Looping.Call6".into(),
-            Notification::LoopingCall7 => "ms-winsoundevent:Notification.Looping.Call7".into(),
-            Notification::LoopingCall8 => "ms-winsoundevent:Notification.Looping.Call8".into(),
-            Notification::LoopingCall9 => "ms-winsoundevent:Notification.Looping.Call9".into(),
-            Notification::LoopingCall10 => "ms-winsoundevent:Notification.Looping.Call10".into(),
-        }
+    fn from_attr(value: &str) -> Option<Notification> {
+        let notification = match value {
+            "ms-winsoundevent:Notification.Default" => Notification::Default,
+            "ms-winsoundevent:Notification.IM" => Notification::IM,
+            "ms-winsoundevent:Notification.Mail" => Notification::Mail,
+            "ms-winsoundevent:Notification.Reminder" => Notification::Reminder,
+            "ms-winsoundevent:Notification.SMS" => Notification::SMS,
+            "ms-winsoundevent:Notification.Looping.Alarm" => Notification::LoopingAlarm,
+            "ms-winsoundevent:Notification.Looping.Alarm2" => Notification::LoopingAlarm2,
+            "ms-winsoundevent:Notification.Looping.Alarm3" => Notification::LoopingAlarm3,
+            "ms-winsoundevent:Notification.Looping.Alarm4" => Notification::LoopingAlarm4,
+            "ms-winsoundevent:Notification.Looping.Alarm5" => Notification::LoopingAlarm5,
+            "ms-winsoundevent:Notification.Looping.Alarm6" => Notification::LoopingAlarm6,
+            "ms-winsoundevent:Notification.Looping.Alarm7" => Notification::LoopingAlarm7,
+            "ms-winsoundevent:Notification.Looping.Alarm8" => Notification::LoopingAlarm8,
+            "ms-winsoundevent:Notification.Looping.Alarm9" => Notification::LoopingAlarm9,
+            "ms-winsoundevent:Notification.Looping.Alarm10" => Notification::LoopingAlarm10,
+            "ms-winsoundevent:Notification.Looping.Call" => Notification::LoopingCall,
+            "ms-winsoundevent:Notification.Looping.Call2" => Notification::LoopingCall2,
+            "ms-winsoundevent:Notification.Looping.Call3" => Notification::LoopingCall3,
+            "ms-winsoundevent:Notification.Looping.Call4" => Notification::LoopingCall4,
+            "ms-winsoundevent:Notification.Looping.Call5" => Notification::LoopingCall5,
+            "ms-winsoundevent:Notification.Looping.Call6" => Notification::LoopingCall6,
+            "ms-winsoundevent:Notification.Looping.Call7" => Notification::LoopingCall7,
+            "ms-winsoundevent:Notification.Looping.Call8" => Notification::LoopingCall8,
+            "ms-winsoundevent:Notification.Looping.Call9" => Notification::LoopingCall9,
+            "ms-winsoundevent:Notification.Looping.Call10" => Notification::LoopingCall10,
+            _ => return None,
+        };
+        Some(notification)
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct Audio {
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let src = match self {
+            Notification::Default => "ms-winsoundevent:Notification.Default",
+            Notification::IM => "ms-winsoundevent:Notification.IM",
+            Notification::Mail => "ms-winsoundevent:Notification.Mail",
+            Notification::Reminder => "ms-winsoundevent:Notification.Reminder",
+            Notification::SMS => "ms-winsoundevent:Notification.SMS",
+            Notification::LoopingAlarm => "ms-winsoundevent:Notification.Looping.Alarm",
+            Notification::LoopingAlarm2 => "ms-winsoundevent:Notification.Looping.Alarm2",
+            Notification::LoopingAlarm3 => "ms-winsoundevent:Notification.Looping.Alarm3",
+            Notification::LoopingAlarm4 => "ms-winsoundevent:Notification.Looping.Alarm4",
+            Notification::LoopingAlarm5 => "ms-winsoundevent:Notification.Looping.Alarm5",
+            Notification::LoopingAlarm6 => "ms-winsoundevent:Notification.Looping.Alarm6",
+            Notification::LoopingAlarm7 => "ms-winsoundevent:Notification.Looping.Alarm7",
+            Notification::LoopingAlarm8 => "ms-winsoundevent:Notification.Looping.Alarm8",
+            Notification::LoopingAlarm9 => "ms-winsoundevent:Notification.Looping.Alarm9",
+            Notification::LoopingAlarm10 => "ms-winsoundevent:Notification.Looping.Alarm10",
+            Notification::LoopingCall => "ms-winsoundevent:Notification.Looping.Call",
+            Notification::LoopingCall2 => "ms-winsoundevent:Notification.Looping.Call2",
+            Notification::LoopingCall3 => "ms-winsoundevent:Notification.Looping.Call3",
+            Notification::LoopingCall4 => "ms-winsoundevent:Notification.Looping.Call4",
+            Notification::LoopingCall5 => "ms-winsoundevent:Notification.Looping.Call5",
+            Notification::LoopingCall6 => "ms-winsoundevent:Notification.Looping.Call6",
+            Notification::LoopingCall7 => "ms-winsoundevent:Notification.Looping.Call7",
+            Notification::LoopingCall8 => "ms-winsoundevent:Notification.Looping.Call8",
+            Notification::LoopingCall9 => "ms-winsoundevent:Notification.Looping.Call9",
+            Notification::LoopingCall10 => "ms-winsoundevent:Notification.Looping.Call10",
+        };
+        write!(f, "{}", src)
+    }
+}
+
+/// Typestate for audio that plays once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Once;
+
+/// Typestate for [looping](Audio::loop_) audio, which only an alarm or incoming call can play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Looping;
+
+/// Typestate for looping audio that makes the toast [long](Audio::long_duration), so any toast can play it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LongLooping;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct Audio<L = Once> {
     /// Set to true if the sound should repeat as long as the toast is shown; false to play only once.
     /// If this attribute is set to true, the duration attribute in the toast element must also be set.
     /// There are specific sounds provided to be used when looping.
     /// Note that UWP apps support neither looping audio nor long-duration toasts.
-    loop_: bool,
+    #[cfg_attr(feature = "serde", serde(default, rename = "loop"))]
+    pub(crate) loop_: bool,
     /// True to mute the sound; false to allow the toast notification sound to play.
-    silent: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) silent: bool,
     /// The media file to play in place of the default sound.
-    src: Notification,
+    pub(crate) src: Notification,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) phantom: PhantomData<L>,
 }
 
 impl Audio {
@@ -111,6 +180,8 @@ impl Audio {
             loop_: false,
             silent: false,
             src,
+            extra: Extra::default(),
+            phantom: PhantomData,
         }
     }
 
@@ -118,9 +189,30 @@ impl Audio {
     /// If this attribute is set to true, the duration attribute in the toast element must also be set.
     /// There are specific sounds provided to be used when looping.
     /// Note that UWP apps support neither looping audio nor long-duration toasts.
-    pub fn loop_(mut self) -> Self {
+    ///
+    /// Looping audio can only be added to an alarm or incoming call, unless it is made [long](Audio::long_duration).
+    pub fn loop_(mut self) -> Audio<Looping> {
         self.loop_ = true;
-        self
+        self.into_loop()
+    }
+}
+
+impl Audio<Looping> {
+    /// Sets the duration of the toast to [`Duration::Long`] when the audio is added, so the audio can loop on any toast.
+    pub fn long_duration(self) -> Audio<LongLooping> {
+        self.into_loop()
+    }
+}
+
+impl<L> Audio<L> {
+    fn into_loop<M>(self) -> Audio<M> {
+        Audio {
+            loop_: self.loop_,
+            silent: self.silent,
+            src: self.src,
+            extra: self.extra,
+            phantom: PhantomData,
+        }
     }
 
     /// True to mute the sound; false to allow the toast notification sound to play.
@@ -128,37 +220,157 @@ impl Audio {
         self.silent = true;
         self
     }
+
+    /// The sound that is played.
+    pub fn get_src(&self) -> Notification {
+        self.src
+    }
+
+    /// Whether the sound repeats as long as the toast is shown.
+    pub fn is_loop(&self) -> bool {
+        self.loop_
+    }
+
+    /// Whether the sound is muted.
+    pub fn is_silent(&self) -> bool {
+        self.silent
+    }
+
+    /// Anything that was not understood when parsing.
+    pub fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
-impl ToXML for Audio {
-    fn into_raw(self, doc: &XmlDocument) -> Result<windows::Data::Xml::Dom::XmlElement> {
-        let audio_node = doc.CreateElement(&"audio".into()).unwrap();
-        audio_node.SetAttribute(&"src".into(), &self.src.to_string().into())?;
+impl<L> ToXML for Audio<L> {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut audio_node = BytesStart::new("audio");
+        audio_node.push_attribute(("src", self.src.to_string().as_str()));
 
         if self.loop_ {
-            audio_node.SetAttribute(&"loop".into(), &"true".into())?;
+            audio_node.push_attribute(("loop", "true"));
         }
 
         if self.silent {
-            audio_node.SetAttribute(&"silent".into(), &"true".into())?;
+            audio_node.push_attribute(("silent", "true"));
         }
 
-        return Ok(audio_node);
+        self.extra.push_attributes(&mut audio_node);
+        write_empty(writer, audio_node, &self.extra)
     }
 }
 
-pub struct HasAudio;
+impl FromXML for Audio {
+    /// Audio with a custom `src` is kept raw, as [`Notification`] only covers the system sounds.
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
+        }
+        let mut attributes = Attributes::new(element);
+        let src = attributes.take_with("src", Notification::from_attr)?;
+        let loop_ = attributes.take_bool("loop");
+        let silent = attributes.take_bool("silent");
+        Some(Audio {
+            loop_,
+            silent,
+            src,
+            extra: attributes.into_extra(element.elements().cloned().collect()),
+            phantom: PhantomData,
+        })
+    }
+}
+
+/// Audio that can be added to a toast with the scenario `C`.
+pub trait AudioFor<C> {
+    /// The audio as it is stored on the toast, setting the duration if the audio needs it.
+    fn apply(self, duration: &mut Option<Duration>) -> Audio;
+}
+
+impl<C> AudioFor<C> for Audio<Once> {
+    fn apply(self, _duration: &mut Option<Duration>) -> Audio {
+        self
+    }
+}
+
+impl<C: LoopingScenario> AudioFor<C> for Audio<Looping> {
+    fn apply(self, _duration: &mut Option<Duration>) -> Audio {
+        self.into_loop()
+    }
+}
+
+impl<C> AudioFor<C> for Audio<LongLooping> {
+    fn apply(self, duration: &mut Option<Duration>) -> Audio {
+        *duration = Some(Duration::Long);
+        self.into_loop()
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// The audio of the toast, if any.
+    pub fn audio(&self) -> Option<&Audio> {
+        self.audio.as_ref()
+    }
+}
 
-impl Toast<()> {
+impl<C> Toast<(), C> {
     /// add custom audio to the toast notification.
-    pub fn add_audio(self, audio: Audio) -> Result<Toast<HasAudio>> {
-        self.doc
-            .SelectSingleNode(&"/toast".into())?
-            .AppendChild(&audio.into_raw(&self.doc)?)?;
-        return Ok(Toast {
-            doc: self.doc,
-            app_id: self.app_id,
-            phantom: PhantomData,
-        });
+    ///
+    /// [Looping](Audio::loop_) audio needs an alarm or incoming call [scenario](Toast::scenario), or a [long duration](Audio::long_duration).
+    ///
+    /// A toast can only have one audio element, so this can only be called once.
+    /// Every other builder method is still available on the returned toast.
+    ///
+    /// ```compile_fail
+    /// # use windows_notifier::{tags::audio::{Audio, Notification}, Toast};
+    /// let toast = Toast::new()?.add_audio(Audio::new(Notification::Default))?;
+    /// let toast = toast.add_audio(Audio::new(Notification::Mail))?;
+    /// # Ok::<(), windows_notifier::error::Error>(())
+    /// ```
+    ///
+    /// Looping audio on a toast that is not an alarm or incoming call does not compile either:
+    ///
+    /// ```compile_fail
+    /// # use windows_notifier::{tags::audio::{Audio, Notification}, tags::toast::Urgent, Toast};
+    /// let toast = Toast::new()?.scenario(Urgent)?;
+    /// let toast = toast.add_audio(Audio::new(Notification::LoopingAlarm).loop_())?;
+    /// # Ok::<(), windows_notifier::error::Error>(())
+    /// ```
+    pub fn add_audio(mut self, audio: impl AudioFor<C>) -> Result<Toast<HasAudio, C>> {
+        self.audio = Some(audio.apply(&mut self.duration));
+        Ok(self.into_state())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::audio::{Audio, Notification};
+    use crate::tags::toast::Alarm;
+    use crate::utils::into_raw::round_trip;
+    use crate::Toast;
+
+    #[test]
+    fn writes_audio() {
+        let audio = Audio::new(Notification::Mail).silent();
+        let (xml, parsed) = round_trip(&audio);
+        assert_eq!(
+            xml,
+            r#"<audio src="ms-winsoundevent:Notification.Mail" silent="true"/>"#
+        );
+        assert_eq!(parsed, audio);
+
+        let toast = Toast::new()
+            .unwrap()
+            .scenario(Alarm)
+            .unwrap()
+            .add_audio(Audio::new(Notification::LoopingAlarm2).loop_())
+            .unwrap();
+        let audio = toast.audio().unwrap();
+        assert!(audio.is_loop());
+        let (xml, parsed) = round_trip(audio);
+        assert_eq!(
+            xml,
+            r#"<audio src="ms-winsoundevent:Notification.Looping.Alarm2" loop="true"/>"#
+        );
+        assert_eq!(&parsed, audio);
     }
 }
@@ -1,12 +1,177 @@
-use windows::Data::Xml::Dom::XmlElement;
+use std::io::Write;
+
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
 
 use crate::error::Result;
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_children, ToXML};
 use crate::Toast;
 
-impl Toast {
-    fn binding_get_element(&self) -> Result<XmlElement> {
-        let res = self.doc.GetElementById(&"binding".into())?;
-        Ok(res)
+use super::image::Image;
+use super::progress::Progress;
+use super::sub_group::Group;
+use super::text::Text;
+
+/// A piece of content inside the binding.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum BindingChild {
+    Text(Text),
+    Image(Image),
+    Group(Group),
+    Progress(Progress),
+}
+
+impl BindingChild {
+    fn from_raw(element: &RawElement) -> Option<BindingChild> {
+        match element.name.as_str() {
+            "text" => Text::from_raw(element).map(BindingChild::Text),
+            "image" => Image::from_raw(element).map(BindingChild::Image),
+            "group" => Group::from_raw(element).map(BindingChild::Group),
+            "progress" => Progress::from_raw(element).map(BindingChild::Progress),
+            _ => None,
+        }
+    }
+}
+
+impl ToXML for BindingChild {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        match self {
+            BindingChild::Text(text) => text.write_xml(writer),
+            BindingChild::Image(image) => image.write_xml(writer),
+            BindingChild::Group(group) => group.write_xml(writer),
+            BindingChild::Progress(progress) => progress.write_xml(writer),
+        }
+    }
+}
+
+/// The `binding` element of the toast, always using the `ToastGeneric` template.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
+pub struct Binding {
+    /// The template used to lay out the content.
+    pub(crate) template: String,
+    /// The target locale of the binding, overriding the one on the visual.
+    pub(crate) lang: Option<String>,
+    /// Set to "true" to allow Windows to append a query string to the image URI supplied in the toast notification.
+    pub(crate) add_image_query: bool,
+    /// A default base URI that is combined with relative URIs in image source attributes.
+    pub(crate) base_uri: Option<String>,
+    /// A template to use if the primary template cannot be found, for use with backward compatibility.
+    pub(crate) fallback: Option<String>,
+    /// The content of the binding, in document order.
+    pub(crate) children: Vec<BindingChild>,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
+}
+
+impl Default for Binding {
+    fn default() -> Self {
+        Binding::new()
+    }
+}
+
+impl Binding {
+    /// A `ToastGeneric` binding with an empty title.
+    pub(crate) fn new() -> Binding {
+        Binding {
+            template: "ToastGeneric".into(),
+            lang: None,
+            add_image_query: false,
+            base_uri: None,
+            fallback: None,
+            children: vec![BindingChild::Text(Text::title(""))],
+            extra: Extra::default(),
+        }
+    }
+
+    /// Anything that was not understood when parsing.
+    pub fn extra(&self) -> &Extra {
+        &self.extra
+    }
+}
+
+impl ToXML for Binding {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut binding = BytesStart::new("binding");
+        binding.push_attribute(("template", self.template.as_str()));
+        if let Some(lang) = &self.lang {
+            binding.push_attribute(("lang", lang.as_str()));
+        }
+        if self.add_image_query {
+            binding.push_attribute(("addImageQuery", "true"));
+        }
+        if let Some(base_uri) = &self.base_uri {
+            binding.push_attribute(("baseUri", base_uri.as_str()));
+        }
+        if let Some(fallback) = &self.fallback {
+            binding.push_attribute(("fallback", fallback.as_str()));
+        }
+        self.extra.push_attributes(&mut binding);
+        write_children(writer, binding, |writer| {
+            for child in &self.children {
+                child.write_xml(writer)?;
+            }
+            self.extra.write_elements(writer)
+        })
+    }
+}
+
+impl FromXML for Binding {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
+        }
+        let mut attributes = Attributes::new(element);
+        let template = attributes.take("template")?;
+        let lang = attributes.take("lang");
+        let add_image_query = attributes.take_bool("addImageQuery");
+        let base_uri = attributes.take("baseUri");
+        let fallback = attributes.take("fallback");
+
+        let mut children = Vec::new();
+        let mut unknown = Vec::new();
+        for child in element.elements() {
+            match BindingChild::from_raw(child) {
+                Some(child) => children.push(child),
+                None => unknown.push(child.clone()),
+            }
+        }
+
+        Some(Binding {
+            template,
+            lang,
+            add_image_query,
+            base_uri,
+            fallback,
+            children,
+            extra: attributes.into_extra(unknown),
+        })
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// The text, images, groups and progress bars of the toast, in the order they are shown.
+    pub fn binding_children(&self) -> &[BindingChild] {
+        &self.visual.binding.children
+    }
+
+    /// The content of the toast, for editing in place.
+    pub fn binding_children_mut(&mut self) -> &mut Vec<BindingChild> {
+        &mut self.visual.binding.children
     }
 
     /// Set to "true" to allow Windows to append a query string to the image URI supplied in the toast notification. Use this attribute if your server hosts images and can handle query strings, either by retrieving an image variant based on the query strings or by ignoring the query string and returning the image as specified without the query string. This query string specifies scale, contrast setting, and language; for instance, a value of
@@ -16,23 +181,26 @@ impl Toast {
     /// given in the notification becomes
     ///
     /// "www.website.com/images/hello.png?ms-scale=100&ms-contrast=standard&ms-lang=en-us"
-    pub fn binding_add_image_query(&self) -> Result<()> {
-        let element = self.binding_get_element()?;
-        element.SetAttribute(&"addImageQuery".into(), &"true".into())?;
+    pub fn binding_add_image_query(&mut self) -> Result<()> {
+        self.visual.binding.add_image_query = true;
+        Ok(())
+    }
+
+    /// The target locale of the binding, overriding the one set with [`Toast::visual_lang`].
+    pub fn binding_lang(&mut self, lang: &str) -> Result<()> {
+        self.visual.binding.lang = Some(lang.into());
         Ok(())
     }
 
     /// A default base URI that is combined with relative URIs in image source attributes.
-    pub fn binding_base_uri(&self, base_uri: &str) -> Result<()> {
-        let element = self.binding_get_element()?;
-        element.SetAttribute(&"baseUri".into(), &base_uri.into())?;
+    pub fn binding_base_uri(&mut self, base_uri: &str) -> Result<()> {
+        self.visual.binding.base_uri = Some(base_uri.into());
         Ok(())
     }
 
     /// A template to use if the primary template cannot be found, for use with backward compatibility.
-    pub fn binding_fallback(&self, fallback: &str) -> Result<()> {
-        let element = self.binding_get_element()?;
-        element.SetAttribute(&"fallback".into(), &fallback.into())?;
+    pub fn binding_fallback(&mut self, fallback: &str) -> Result<()> {
+        self.visual.binding.fallback = Some(fallback.into());
         Ok(())
     }
 }
@@ -1,41 +1,74 @@
+use std::io::Write;
+
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
+
 use crate::error::Result;
+use crate::raw::{Attributes, Extra, RawElement};
+use crate::utils::into_raw::{write_children, write_empty};
 use crate::Toast;
 
-#[derive(Debug, Clone, Copy)]
+use super::activation::ActivationType;
+use super::toast::{Alarm, IncomingCall};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum CallCommands {
     Video,
     Voice,
     Decline,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum AlarmCommands {
     Snooze,
     Dismiss,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum Senario {
     Alarm(AlarmCommands),
     IncomingCall(CallCommands),
 }
 
-impl Toast {
-    /// Senario: IncomingCall
-    /// * `command` - Specifies one command from the system-defined command list. These values correspond to available actions that the user can take. Two scenarios are available through the commands element.
-    /// * `arguments` - An argument string that can be passed to the associated app to provide specifics about the action that it should execute in response to the user action.
-    pub fn add_command(&self, scenario: Senario, arguments: String) -> Result<()> {
-        let commands = self.doc.CreateElement(&"commands".into())?;
+impl Senario {
+    /// The value of the `scenario` attribute on the `commands` element.
+    fn scenario_str(&self) -> &'static str {
+        match self {
+            Senario::Alarm(_) => "alarm",
+            Senario::IncomingCall(_) => "incomingCall",
+        }
+    }
 
-        let scenario_str = match scenario {
-            Senario::Alarm(command) => "alarm",
-            Senario::IncomingCall(command) => "incomingCall",
+    fn from_attr(scenario: &str, id: &str) -> Option<Senario> {
+        let senario = match (scenario, id) {
+            ("alarm", "snooze") => Senario::Alarm(AlarmCommands::Snooze),
+            ("alarm", "dismiss") => Senario::Alarm(AlarmCommands::Dismiss),
+            ("incomingCall", "video") => Senario::IncomingCall(CallCommands::Video),
+            ("incomingCall", "voice") => Senario::IncomingCall(CallCommands::Voice),
+            ("incomingCall", "decline") => Senario::IncomingCall(CallCommands::Decline),
+            _ => return None,
         };
-        commands.SetAttribute(&"scenario".into(), &scenario_str.into())?;
-
-        let text_node = self.doc.CreateElement(&"command".into()).unwrap();
+        Some(senario)
+    }
 
-        let id = match scenario {
+    /// The value of the `id` attribute on the `command` element.
+    fn id_str(&self) -> &'static str {
+        match self {
             Senario::Alarm(command) => match command {
                 AlarmCommands::Snooze => "snooze",
                 AlarmCommands::Dismiss => "dismiss",
@@ -45,16 +78,167 @@ impl Toast {
                 CallCommands::Voice => "voice",
                 CallCommands::Decline => "decline",
             },
-        };
+        }
+    }
+}
+
+/// A command that can be added to a toast with the scenario `C`.
+pub trait ScenarioCommand<C> {
+    fn into_senario(self) -> Senario;
+}
+
+impl ScenarioCommand<Alarm> for AlarmCommands {
+    fn into_senario(self) -> Senario {
+        Senario::Alarm(self)
+    }
+}
+
+impl ScenarioCommand<IncomingCall> for CallCommands {
+    fn into_senario(self) -> Senario {
+        Senario::IncomingCall(self)
+    }
+}
+
+/// A system-defined command added with [`Toast::add_command`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct Command {
+    pub(crate) scenario: Senario,
+    pub(crate) arguments: String,
+}
+
+impl Command {
+    /// The command and the scenario it belongs to.
+    pub fn get_scenario(&self) -> Senario {
+        self.scenario
+    }
+
+    /// How the command activates: windows handles the alarm commands itself,
+    /// the call commands launch the app in the foreground with the arguments.
+    pub fn get_activation_type(&self) -> ActivationType {
+        match self.scenario {
+            Senario::Alarm(_) => ActivationType::System,
+            Senario::IncomingCall(_) => ActivationType::Foreground,
+        }
+    }
 
-        text_node.SetAttribute(&"id".into(), &id.into())?;
-        text_node.SetAttribute(&"arguments".into(), &arguments.into())?;
+    /// The arguments passed to the app when the command is used.
+    pub fn get_arguments(&self) -> &str {
+        &self.arguments
+    }
+
+    /// Reads a `commands` element, returning `None` if any part of it is not understood.
+    pub(crate) fn read_commands(element: &RawElement) -> Option<Vec<Command>> {
+        if element.has_text() {
+            return None;
+        }
+        let mut attributes = Attributes::new(element);
+        let scenario = attributes.take("scenario")?;
+        if !attributes.into_extra(Vec::new()).is_empty() {
+            return None;
+        }
 
-        commands.AppendChild(&text_node)?;
+        let mut commands = Vec::new();
+        for child in element.elements() {
+            if child.name != "command" || child.has_text() || child.elements().next().is_some() {
+                return None;
+            }
+            let mut attributes = Attributes::new(child);
+            let id = attributes.take("id")?;
+            let arguments = attributes.take("arguments")?;
+            if !attributes.into_extra(Vec::new()).is_empty() {
+                return None;
+            }
+            commands.push(Command {
+                scenario: Senario::from_attr(&scenario, &id)?,
+                arguments,
+            });
+        }
+        (!commands.is_empty()).then_some(commands)
+    }
 
-        self.doc
-            .SelectSingleNode(&"/toast".into())?
-            .AppendChild(&commands)?;
+    /// Writes all commands into a single `commands` element, using the scenario of the first command.
+    pub(crate) fn write_commands<W: Write>(
+        commands: &[Command],
+        writer: &mut Writer<W>,
+    ) -> Result<()> {
+        let mut commands_node = BytesStart::new("commands");
+        if let Some(first) = commands.first() {
+            commands_node.push_attribute(("scenario", first.scenario.scenario_str()));
+        }
+        write_children(writer, commands_node, |writer| {
+            for command in commands {
+                let mut command_node = BytesStart::new("command");
+                command_node.push_attribute(("id", command.scenario.id_str()));
+                command_node.push_attribute(("arguments", command.arguments.as_str()));
+                write_empty(writer, command_node, &Extra::default())?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// The system commands of the toast.
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Senario: Alarm or IncomingCall, the command has to match the [scenario](Toast::scenario) of the toast.
+    /// * `command` - Specifies one command from the system-defined command list. These values correspond to available actions that the user can take. Two scenarios are available through the commands element.
+    /// * `arguments` - An argument string that can be passed to the associated app to provide specifics about the action that it should execute in response to the user action, plain or [`ToastArguments`](crate::arguments::ToastArguments).
+    ///
+    /// A command of another scenario does not compile:
+    ///
+    /// ```compile_fail
+    /// # use windows_notifier::{tags::commands::AlarmCommands, tags::toast::IncomingCall, Toast};
+    /// let mut toast = Toast::new()?.scenario(IncomingCall)?;
+    /// toast.add_command(AlarmCommands::Snooze, "snooze")?;
+    /// # Ok::<(), windows_notifier::error::Error>(())
+    /// ```
+    pub fn add_command(
+        &mut self,
+        command: impl ScenarioCommand<C>,
+        arguments: impl Into<String>,
+    ) -> Result<()> {
+        self.commands.push(Command {
+            scenario: command.into_senario(),
+            arguments: arguments.into(),
+        });
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::commands::{AlarmCommands, CallCommands, Senario};
+    use crate::tags::toast::{Alarm, IncomingCall};
+    use crate::Toast;
+
+    #[test]
+    fn writes_commands() {
+        let mut alarm = Toast::new().unwrap().scenario(Alarm).unwrap();
+        alarm.add_command(AlarmCommands::Snooze, "snooze").unwrap();
+        alarm
+            .add_command(AlarmCommands::Dismiss, "dismiss")
+            .unwrap();
+        let xml = alarm.into_raw().unwrap();
+        assert!(xml.contains(r#"<commands scenario="alarm"><command id="snooze" arguments="snooze"/><command id="dismiss" arguments="dismiss"/></commands>"#));
+        assert_eq!(Toast::from_xml(&xml).unwrap().commands(), alarm.commands());
+
+        let mut call = Toast::new().unwrap().scenario(IncomingCall).unwrap();
+        call.add_command(CallCommands::Video, "video").unwrap();
+        call.add_command(CallCommands::Decline, "decline").unwrap();
+        let xml = call.into_raw().unwrap();
+        assert!(xml.contains(r#"<commands scenario="incomingCall"><command id="video" arguments="video"/><command id="decline" arguments="decline"/></commands>"#));
+        let parsed = Toast::from_xml(&xml).unwrap();
+        assert_eq!(
+            parsed.commands()[1].get_scenario(),
+            Senario::IncomingCall(CallCommands::Decline)
+        );
+    }
+}
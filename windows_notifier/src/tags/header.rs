@@ -1,69 +1,182 @@
-use crate::Toast;
-use crate::error::Result;
+use std::io::Write;
 
-/// The type of activation this header will use when clicked.
-#[derive(Debug, Clone, Copy, Default)]
-pub enum ActivationType {
-    /// Default value. Your foreground app is launched.
-    #[default]
-    Forground,
-    /// Launch a different app using protocol activation.
-    Protocol,
-}
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
 
-impl ActivationType {
-    fn to_string(&self) -> String {
-        match self {
-            Self::Forground => "foreground".to_string(),
-            Self::Protocol => "protocol".to_string(),
-        }
-    }
-}
+use crate::error::Result;
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_empty, ToXML};
+use crate::Toast;
+
+use super::activation::{protocol_uri, ActivationType};
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct Header {
     /// A developer-created identifier that uniquely identifies this header.
     /// If two notifications have the same header id, they will be displayed underneath the same header in Action Center.
-    id: String,
+    pub(crate) id: String,
     /// A title for the header.
-    title: String,
+    pub(crate) title: String,
     /// A developer-defined string of arguments that is returned to the app when the user clicks this header. Cannot be null.
-    arguments: String,
-    /// The type of activation this header will use when clicked.
-    activation_type: Option<ActivationType>,
+    pub(crate) arguments: String,
+    /// The type of activation this header will use when clicked, foreground or protocol.
+    pub(crate) activation_type: Option<ActivationType>,
+    /// The package family name of the app that handles a protocol activation.
+    pub(crate) target_application_pfn: Option<String>,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
 }
 
 impl Header {
-    pub fn new(id: &str, title: &str, arguments: &str) -> Header {
+    /// `arguments` can be a plain string or [`ToastArguments`](crate::arguments::ToastArguments).
+    pub fn new(id: &str, title: &str, arguments: impl Into<String>) -> Header {
         Header {
             id: id.into(),
             title: title.into(),
             arguments: arguments.into(),
             activation_type: None,
+            target_application_pfn: None,
+            extra: Extra::default(),
         }
     }
 
-    /// The type of activation this header will use when clicked.
+    /// A header launching `uri` with protocol activation when clicked.
+    ///
+    /// Returns [`Error::InvalidArgument`](crate::error::Error::InvalidArgument) if `uri` is not an absolute URI.
+    pub fn protocol(id: &str, title: &str, uri: &str) -> Result<Header> {
+        let uri = protocol_uri("toast/header", uri)?;
+        Ok(Header::new(id, title, uri).activation_type(ActivationType::Protocol))
+    }
+
+    /// The type of activation this header will use when clicked, foreground or protocol.
     pub fn activation_type(mut self, activation_type: ActivationType) -> Self {
         self.activation_type = Some(activation_type);
         self
     }
+
+    /// The package family name of the app that handles the protocol activation of this header.
+    pub fn target_application_pfn(mut self, pfn: impl Into<String>) -> Self {
+        self.target_application_pfn = Some(pfn.into());
+        self
+    }
+
+    /// The identifier of the header.
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    /// The title of the header.
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    /// The arguments returned to the app when the user clicks this header.
+    pub fn get_arguments(&self) -> &str {
+        &self.arguments
+    }
+
+    /// The type of activation this header will use when clicked.
+    pub fn get_activation_type(&self) -> Option<ActivationType> {
+        self.activation_type
+    }
+
+    /// The package family name of the app that handles the protocol activation.
+    pub fn get_target_application_pfn(&self) -> Option<&str> {
+        self.target_application_pfn.as_deref()
+    }
+
+    /// Anything that was not understood when parsing.
+    pub fn extra(&self) -> &Extra {
+        &self.extra
+    }
+}
+
+impl ToXML for Header {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut header_node = BytesStart::new("header");
+        header_node.push_attribute(("id", self.id.as_str()));
+        header_node.push_attribute(("title", self.title.as_str()));
+        header_node.push_attribute(("arguments", self.arguments.as_str()));
+        if let Some(activation_type) = self.activation_type {
+            header_node.push_attribute(("activationType", activation_type.to_string().as_str()));
+        }
+        if let Some(pfn) = &self.target_application_pfn {
+            header_node.push_attribute(("protocolActivationTargetApplicationPfn", pfn.as_str()));
+        }
+        self.extra.push_attributes(&mut header_node);
+        write_empty(writer, header_node, &self.extra)
+    }
 }
 
-impl Toast {
-    pub fn add_header(&self, header: Header) -> Result<()> {
-        let header_node = self.doc.CreateElement(&"header".into())?;
-        header_node.SetAttribute(&"id".into(), &header.id.into())?;
-        header_node.SetAttribute(&"title".into(), &header.title.into())?;
-        header_node.SetAttribute(&"arguments".into(), &header.arguments.into())?;
-        if let Some(activation_type) = header.activation_type {
-            header_node.SetAttribute(
-                &"activationType".into(),
-                &activation_type.to_string().into(),
-            )?;
+impl FromXML for Header {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
         }
-        self.doc
-            .SelectSingleNode(&"/toast".into())?
-            .AppendChild(&header_node)?;
+        let mut attributes = Attributes::new(element);
+        let id = attributes.take("id")?;
+        let title = attributes.take("title")?;
+        let arguments = attributes.take("arguments")?;
+        let activation_type = attributes.take_with("activationType", ActivationType::from_attr);
+        let target_application_pfn = attributes.take("protocolActivationTargetApplicationPfn");
+        Some(Header {
+            id,
+            title,
+            arguments,
+            activation_type,
+            target_application_pfn,
+            extra: attributes.into_extra(element.elements().cloned().collect()),
+        })
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// The header of the toast, if any.
+    pub fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
+
+    /// Set the header of the toast notification, replacing any header that was set before.
+    pub fn add_header(&mut self, header: Header) -> Result<()> {
+        self.header = Some(header);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::activation::ActivationType;
+    use crate::tags::header::Header;
+    use crate::utils::into_raw::round_trip;
+
+    #[test]
+    fn writes_header() {
+        let header = Header::new("6289", "Camping!!", "action=openConversation&id=6289");
+        let (xml, parsed) = round_trip(&header);
+        assert_eq!(
+            xml,
+            r#"<header id="6289" title="Camping!!" arguments="action=openConversation&amp;id=6289"/>"#
+        );
+        assert_eq!(parsed, header);
+
+        let header = Header::protocol("6289", "Camping!!", "https://example.com/camping")
+            .unwrap()
+            .target_application_pfn("Microsoft.WindowsStore_8wekyb3d8bbwe");
+        let (xml, parsed) = round_trip(&header);
+        assert_eq!(
+            xml,
+            r#"<header id="6289" title="Camping!!" arguments="https://example.com/camping" activationType="protocol" protocolActivationTargetApplicationPfn="Microsoft.WindowsStore_8wekyb3d8bbwe"/>"#
+        );
+        assert_eq!(parsed.get_activation_type(), Some(ActivationType::Protocol));
+        assert_eq!(parsed, header);
+    }
+}
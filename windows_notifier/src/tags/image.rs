@@ -1,10 +1,27 @@
+use std::fmt;
+use std::io::Write;
+
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
+
 use crate::error::Result;
-use crate::utils::into_raw::ToXML;
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_empty, ToXML};
 use crate::Toast;
 
+use super::binding::BindingChild;
+
 /// The placement of the image.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum Placement {
+    /// The image is displayed inline, below the text. Written as no placement attribute.
+    #[default]
+    Inline,
     /// The image replaces your app's logo in the toast notification.
     AppLogoOverride,
     ///  The image is displayed as a hero image.
@@ -12,33 +29,65 @@ pub enum Placement {
 }
 
 impl Placement {
-    fn to_string(&self) -> String {
+    fn from_attr(value: &str) -> Option<Placement> {
+        match value {
+            "appLogoOverride" => Some(Placement::AppLogoOverride),
+            "hero" => Some(Placement::Hero),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Placement::AppLogoOverride => "appLogoOverride".into(),
-            Placement::Hero => "hero".into(),
+            Placement::Inline => write!(f, ""),
+            Placement::AppLogoOverride => write!(f, "appLogoOverride"),
+            Placement::Hero => write!(f, "hero"),
         }
     }
 }
 
 /// The cropping of the image.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum Crop {
     /// The image is not cropped and displayed as a square.
+    #[default]
     None,
     /// The image is cropped into a circle.
     Circle,
 }
 
 impl Crop {
-    fn to_string(&self) -> String {
+    fn from_attr(value: &str) -> Option<Crop> {
+        match value {
+            "none" => Some(Crop::None),
+            "circle" => Some(Crop::Circle),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Crop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Crop::None => "".into(),
-            Crop::Circle => "circle".into(),
+            Crop::None => write!(f, ""),
+            Crop::Circle => write!(f, "circle"),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct Image {
     /// Set to "true" to allow Windows to append a query string to the image URI supplied in the toast notification. Use this attribute if your server hosts images and can handle query strings, either by retrieving an image variant based on the query strings or by ignoring the query string and returning the image as specified without the query string. This query string specifies scale, contrast setting, and language; for instance, a value of
     ///
@@ -47,11 +96,15 @@ pub struct Image {
     /// given in the notification becomes
     ///
     /// "www.website.com/images/hello.png?ms-scale=100&ms-contrast=standard&ms-lang=en-us"
-    add_image_query: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) add_image_query: bool,
     /// A description of the image, for users of assistive technologies.
-    alt: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) alt: String,
+    /// The message id the alt text is [localized](crate::localize) from, not part of the xml.
+    pub(crate) alt_message: Option<String>,
     /// The image element in the toast template that this image is intended for. If a template has only one image, then this value is 1. The number of available image positions is based on the template definition.
-    id: Option<i32>,
+    pub(crate) id: Option<i32>,
     /// The URI of the image source, using one of these protocol handlers:
     ///
     ///    http:// or https://
@@ -69,11 +122,19 @@ pub struct Image {
     ///    file:///
     ///
     ///    A local image. (Supported only for desktop apps. This protocol cannot be used by UWP apps.)
-    src: String,
+    pub(crate) src: String,
     /// The placement of the image.
-    placement: Placement,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) placement: Placement,
     /// The cropping of the image.
-    hint_crop: Crop,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) hint_crop: Crop,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
 }
 
 impl Image {
@@ -81,10 +142,12 @@ impl Image {
         Self {
             add_image_query: false,
             alt: "".into(),
+            alt_message: None,
             id: None,
             src: src.into(),
             placement: Placement::AppLogoOverride,
             hint_crop: Crop::None,
+            extra: Extra::default(),
         }
     }
 
@@ -98,6 +161,12 @@ impl Image {
         self
     }
 
+    /// Replace the alt text with message `id` when the toast is [localized](crate::Toast::localize).
+    pub fn set_alt_message(mut self, id: impl Into<String>) -> Self {
+        self.alt_message = Some(id.into());
+        self
+    }
+
     pub fn set_placement(mut self, placement: Placement) -> Self {
         self.placement = placement;
         self
@@ -107,34 +176,128 @@ impl Image {
         self.hint_crop = hint_crop;
         self
     }
+
+    /// The URI of the image source.
+    pub fn get_src(&self) -> &str {
+        &self.src
+    }
+
+    /// A description of the image, for users of assistive technologies.
+    pub fn get_alt(&self) -> &str {
+        &self.alt
+    }
+
+    /// The message id the alt text is localized from.
+    pub fn get_alt_message(&self) -> Option<&str> {
+        self.alt_message.as_deref()
+    }
+
+    /// The id of the image element.
+    pub fn get_id(&self) -> Option<i32> {
+        self.id
+    }
+
+    /// The placement of the image.
+    pub fn get_placement(&self) -> Placement {
+        self.placement
+    }
+
+    /// The cropping of the image.
+    pub fn get_hint_crop(&self) -> Crop {
+        self.hint_crop
+    }
+
+    /// Anything that was not understood when parsing.
+    pub fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 impl ToXML for Image {
-    fn into_raw(
-        self,
-        doc: &windows::Data::Xml::Dom::XmlDocument,
-    ) -> Result<windows::Data::Xml::Dom::XmlElement> {
-        let text_node = doc.CreateElement(&"image".into()).unwrap();
-        text_node.SetAttribute(
-            &"addImageQuery".into(),
-            &self.add_image_query.to_string().into(),
-        )?;
-        text_node.SetAttribute(&"alt".into(), &self.alt.into())?;
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut image_node = BytesStart::new("image");
+        if self.add_image_query {
+            image_node.push_attribute(("addImageQuery", "true"));
+        }
+        if !self.alt.is_empty() {
+            image_node.push_attribute(("alt", self.alt.as_str()));
+        }
         if let Some(id) = self.id {
-            text_node.SetAttribute(&"id".into(), &id.to_string().into())?;
+            image_node.push_attribute(("id", id.to_string().as_str()));
         }
-        text_node.SetAttribute(&"src".into(), &self.src.into())?;
-        text_node.SetAttribute(&"placement".into(), &self.placement.to_string().into())?;
-        text_node.SetAttribute(&"hint-crop".into(), &self.hint_crop.to_string().into())?;
-        return Ok(text_node);
+        image_node.push_attribute(("src", self.src.as_str()));
+        if self.placement != Placement::Inline {
+            image_node.push_attribute(("placement", self.placement.to_string().as_str()));
+        }
+        if self.hint_crop != Crop::None {
+            image_node.push_attribute(("hint-crop", self.hint_crop.to_string().as_str()));
+        }
+        self.extra.push_attributes(&mut image_node);
+        write_empty(writer, image_node, &self.extra)
+    }
+}
+
+impl FromXML for Image {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
+        }
+        let mut attributes = Attributes::new(element);
+        let src = attributes.take("src")?;
+        let add_image_query = attributes.take_bool("addImageQuery");
+        let alt = attributes.take("alt").unwrap_or_default();
+        let id = attributes.take_with("id", |id| id.parse().ok());
+        let placement = attributes
+            .take_with("placement", Placement::from_attr)
+            .unwrap_or(Placement::Inline);
+        let hint_crop = attributes
+            .take_with("hint-crop", Crop::from_attr)
+            .unwrap_or(Crop::None);
+        let extra = attributes.into_extra(element.elements().cloned().collect());
+        Some(Image {
+            add_image_query,
+            alt,
+            alt_message: None,
+            id,
+            src,
+            placement,
+            hint_crop,
+            extra,
+        })
     }
 }
 
-impl<S> Toast<S> {
-    pub fn add_image(&self, image: Image) -> Result<()> {
-        self.doc
-            .SelectSingleNode(&"/toast/visual/binding".into())?
-            .AppendChild(&image.into_raw(&self.doc)?)?;
-        return Ok(());
+impl<S, C> Toast<S, C> {
+    pub fn add_image(&mut self, image: Image) -> Result<()> {
+        self.visual
+            .binding
+            .children
+            .push(BindingChild::Image(image));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::image::{Crop, Image, Placement};
+    use crate::utils::into_raw::round_trip;
+
+    #[test]
+    fn writes_image() {
+        let image = Image::new("https://unsplash.it/64?image=1027")
+            .set_id(2)
+            .set_alt("Jill".into())
+            .set_placement(Placement::AppLogoOverride)
+            .set_hint_crop(Crop::Circle);
+        let (xml, parsed) = round_trip(&image);
+        assert_eq!(
+            xml,
+            r#"<image alt="Jill" id="2" src="https://unsplash.it/64?image=1027" placement="appLogoOverride" hint-crop="circle"/>"#
+        );
+        assert_eq!(parsed, image);
+
+        let (xml, parsed) = round_trip(&Image::new("inline.png").set_placement(Placement::Inline));
+        assert_eq!(xml, r#"<image src="inline.png"/>"#);
+        assert_eq!(parsed.get_placement(), Placement::Inline);
     }
 }
@@ -1,13 +1,44 @@
-use crate::error::Result;
-use crate::utils::into_raw::ToXML;
+use std::io::Write;
+
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
+
+use crate::error::{Error, Result};
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_children, write_empty, ToXML};
+use crate::validate::MAX_INPUTS;
 use crate::Toast;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct Selection {
     /// The ID associated with the selection.
-    id: String,
+    pub(crate) id: String,
     /// The text displayed for the selection.
-    content: String,
+    pub(crate) content: String,
+}
+
+impl Selection {
+    pub fn new(id: impl Into<String>, content: impl Into<String>) -> Selection {
+        Selection {
+            id: id.into(),
+            content: content.into(),
+        }
+    }
+
+    /// The ID associated with the selection.
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    /// The text displayed for the selection.
+    pub fn get_content(&self) -> &str {
+        &self.content
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -16,33 +47,63 @@ pub enum InputType {
     Selection(Vec<Selection>),
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum Input {
     Text {
         /// The ID associated with the input.
         id: String,
         /// Text displayed as a label for the input.
         title: Option<String>,
+        /// The message id the title is [localized](crate::localize) from, not part of the xml.
+        title_message: Option<String>,
         /// The placeholder displayed for text input.
         place_holder_content: Option<String>,
+        /// The message id the placeholder is localized from, not part of the xml.
+        place_holder_message: Option<String>,
+        /// Anything that was not understood when parsing.
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Extra::is_empty")
+        )]
+        extra: Extra,
     },
     Selection {
         /// The ID associated with the input.
         id: String,
         /// Text displayed as a label for the input.
         title: Option<String>,
+        /// The message id the title is localized from, not part of the xml.
+        title_message: Option<String>,
         /// The ID of the selection that is selected by default.
         default_selection: Option<String>,
         /// A list of selections that the user can choose from.
         selections: Vec<Selection>,
+        /// Anything that was not understood when parsing.
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Extra::is_empty")
+        )]
+        extra: Extra,
     },
 }
 
 impl Input {
-    pub fn new_text(id: impl Into<String>, place_holder_content: Option<impl Into<String>>) -> Input {
+    pub fn new_text(
+        id: impl Into<String>,
+        place_holder_content: Option<impl Into<String>>,
+    ) -> Input {
         Input::Text {
             id: id.into(),
             title: None,
+            title_message: None,
             place_holder_content: place_holder_content.map(|s| s.into()),
+            place_holder_message: None,
+            extra: Extra::default(),
         }
     }
 
@@ -54,107 +115,231 @@ impl Input {
         Input::Selection {
             id: id.into(),
             title: None,
+            title_message: None,
             default_selection: default_selection.map(|s| s.into()),
             selections,
+            extra: Extra::default(),
         }
     }
 
     pub fn title(mut self, title: impl Into<String>) -> Self {
-        match self {
-            Input::Text {
-                id,
-                place_holder_content,
-                ..
-            } => {
-                self = Input::Text {
-                    id,
-                    title: Some(title.into()),
-                    place_holder_content,
-                };
+        match &mut self {
+            Input::Text { title: current, .. } | Input::Selection { title: current, .. } => {
+                *current = Some(title.into());
             }
-            Input::Selection {
-                id,
-                default_selection,
-                selections,
-                ..
-            } => {
-                self = Input::Selection {
-                    id,
-                    title: Some(title.into()),
-                    default_selection,
-                    selections,
-                };
+        }
+        self
+    }
+
+    /// Replace the title with message `id` when the toast is [localized](crate::Toast::localize).
+    pub fn title_message(mut self, id: impl Into<String>) -> Self {
+        match &mut self {
+            Input::Text { title_message, .. } | Input::Selection { title_message, .. } => {
+                *title_message = Some(id.into());
             }
         }
         self
     }
+
+    /// Replace the placeholder with message `id` when the toast is [localized](crate::Toast::localize).
+    /// Selection inputs have no placeholder, so this does nothing for them.
+    pub fn place_holder_message(mut self, id: impl Into<String>) -> Self {
+        if let Input::Text {
+            place_holder_message,
+            ..
+        } = &mut self
+        {
+            *place_holder_message = Some(id.into());
+        }
+        self
+    }
+
+    /// The ID associated with the input.
+    pub fn get_id(&self) -> &str {
+        match self {
+            Input::Text { id, .. } | Input::Selection { id, .. } => id,
+        }
+    }
 }
 
 impl ToXML for Input {
-    fn into_raw(
-        self,
-        doc: &windows::Data::Xml::Dom::XmlDocument,
-    ) -> Result<windows::Data::Xml::Dom::XmlElement> {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
         match self {
             Input::Text {
                 id,
                 title,
                 place_holder_content,
+                extra,
+                ..
             } => {
-                let text_input_node = doc.CreateElement(&"input".into()).unwrap();
-                text_input_node.SetAttribute(&"id".into(), &id.into())?;
-                text_input_node.SetAttribute(&"type".into(), &"text".into())?;
+                let mut text_input_node = BytesStart::new("input");
+                text_input_node.push_attribute(("id", id.as_str()));
+                text_input_node.push_attribute(("type", "text"));
 
                 if let Some(title) = title {
-                    text_input_node.SetAttribute(&"title".into(), &title.into())?;
+                    text_input_node.push_attribute(("title", title.as_str()));
                 }
 
                 if let Some(place_holder_content) = place_holder_content {
                     text_input_node
-                        .SetAttribute(&"placeHolderContent".into(), &place_holder_content.into())?;
+                        .push_attribute(("placeHolderContent", place_holder_content.as_str()));
                 }
 
-                return Ok(text_input_node);
+                extra.push_attributes(&mut text_input_node);
+                write_empty(writer, text_input_node, extra)
             }
             Input::Selection {
                 id,
                 title,
                 default_selection,
                 selections,
+                extra,
+                ..
             } => {
-                let selection_input_node = doc.CreateElement(&"input".into()).unwrap();
-                selection_input_node.SetAttribute(&"id".into(), &id.into())?;
-                selection_input_node.SetAttribute(&"type".into(), &"selection".into())?;
+                let mut selection_input_node = BytesStart::new("input");
+                selection_input_node.push_attribute(("id", id.as_str()));
+                selection_input_node.push_attribute(("type", "selection"));
 
                 if let Some(title) = title {
-                    selection_input_node.SetAttribute(&"title".into(), &title.into())?;
+                    selection_input_node.push_attribute(("title", title.as_str()));
                 }
 
                 if let Some(default_selection) = default_selection {
                     selection_input_node
-                        .SetAttribute(&"defaultInput".into(), &default_selection.into())?;
+                        .push_attribute(("defaultInput", default_selection.as_str()));
                 }
 
-                for selection in selections {
-                    let selection_node = doc.CreateElement(&"selection".into()).unwrap();
-                    selection_node.SetAttribute(&"id".into(), &selection.id.into())?;
-                    selection_node.SetAttribute(&"content".into(), &selection.content.into())?;
+                extra.push_attributes(&mut selection_input_node);
+                write_children(writer, selection_input_node, |writer| {
+                    for selection in selections {
+                        let mut selection_node = BytesStart::new("selection");
+                        selection_node.push_attribute(("id", selection.id.as_str()));
+                        selection_node.push_attribute(("content", selection.content.as_str()));
+                        write_empty(writer, selection_node, &Extra::default())?;
+                    }
+                    extra.write_elements(writer)
+                })
+            }
+        }
+    }
+}
 
-                    selection_input_node.AppendChild(&selection_node)?;
-                }
+impl FromXML for Selection {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() || element.elements().next().is_some() {
+            return None;
+        }
+        let mut attributes = Attributes::new(element);
+        let selection = Selection {
+            id: attributes.take("id")?,
+            content: attributes.take("content")?,
+        };
+        attributes
+            .into_extra(Vec::new())
+            .is_empty()
+            .then_some(selection)
+    }
+}
+
+impl FromXML for Input {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
+        }
+        let mut attributes = Attributes::new(element);
+        let id = attributes.take("id")?;
+        let input_type = attributes.take_with("type", |input_type| match input_type {
+            "text" | "selection" => Some(input_type.to_string()),
+            _ => None,
+        })?;
+        let title = attributes.take("title");
 
-                return Ok(selection_input_node);
+        if input_type == "text" {
+            let place_holder_content = attributes.take("placeHolderContent");
+            return Some(Input::Text {
+                id,
+                title,
+                title_message: None,
+                place_holder_content,
+                place_holder_message: None,
+                extra: attributes.into_extra(element.elements().cloned().collect()),
+            });
+        }
+
+        let default_selection = attributes.take("defaultInput");
+        let mut selections = Vec::new();
+        let mut unknown = Vec::new();
+        for child in element.elements() {
+            match (child.name.as_str(), Selection::from_raw(child)) {
+                ("selection", Some(selection)) => selections.push(selection),
+                _ => unknown.push(child.clone()),
             }
         }
+        Some(Input::Selection {
+            id,
+            title,
+            title_message: None,
+            default_selection,
+            selections,
+            extra: attributes.into_extra(unknown),
+        })
     }
 }
 
-impl<S> Toast<S> {
-    pub fn add_input(&self, input: Input) -> Result<()> {
-        let input_node = input.into_raw(&self.doc)?;
-        self.doc
-            .SelectSingleNode(&"/toast/actions".into())?
-            .AppendChild(&input_node)?;
+impl<S, C> Toast<S, C> {
+    /// The inputs of the toast.
+    pub fn inputs(&self) -> &[Input] {
+        &self.actions.inputs
+    }
+
+    /// The inputs of the toast, for editing in place.
+    pub fn inputs_mut(&mut self) -> &mut Vec<Input> {
+        &mut self.actions.inputs
+    }
+
+    /// add an input to the toast notification.
+    /// A toast can have at most [`MAX_INPUTS`] inputs.
+    pub fn add_input(&mut self, input: Input) -> Result<()> {
+        let inputs = &mut self.actions.inputs;
+        if inputs.len() >= MAX_INPUTS {
+            return Err(Error::limit_exceeded(
+                format!("toast/actions/input[{}]", inputs.len() + 1),
+                MAX_INPUTS,
+            ));
+        }
+        inputs.push(input);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::input::{Input, Selection};
+    use crate::utils::into_raw::round_trip;
+
+    #[test]
+    fn writes_inputs() {
+        let input = Input::new_text("reply", Some("Type a reply")).title("Reply");
+        let (xml, parsed) = round_trip(&input);
+        assert_eq!(
+            xml,
+            r#"<input id="reply" type="text" title="Reply" placeHolderContent="Type a reply"/>"#
+        );
+        assert_eq!(parsed, input);
+
+        let input = Input::new_selection(
+            "time",
+            vec![
+                Selection::new("15", "15 minutes"),
+                Selection::new("60", "1 hour"),
+            ],
+            Some("15"),
+        );
+        let (xml, parsed) = round_trip(&input);
+        assert_eq!(
+            xml,
+            r#"<input id="time" type="selection" defaultInput="15"><selection id="15" content="15 minutes"/><selection id="60" content="1 hour"/></input>"#
+        );
+        assert_eq!(parsed, input);
+    }
+}
@@ -6,7 +6,9 @@ pub mod image;
 pub mod audio;
 pub mod commands;
 pub mod action;
+pub mod actions;
+pub mod activation;
 pub mod sub_group;
 pub mod header;
 pub mod input;
-pub mod progress;
\ No newline at end of file
+pub mod progress;
@@ -1,36 +1,89 @@
+use std::io::Write;
+
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
+
 use crate::error::Result;
-use crate::utils::into_raw::ToXML;
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_empty, ToXML};
 use crate::Toast;
 
+use super::binding::BindingChild;
+
 /// The value of the progress bar.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum Value {
     /// This value either be a floating point number between 0.0 and 1.0
     Floating(f32),
     /// results in a loading animation
     Indeterminate,
+    /// The value is read from the toast data with this key, written as `{key}`, so it can be [updated](crate::identity::ToastHandle::update).
+    Binding(String),
+}
+
+/// The `{key}` form of a string that is read from the toast data, for the title, status or value string override.
+pub fn binding(key: &str) -> String {
+    format!("{{{}}}", key)
 }
 
+/// The key of a `{key}` binding.
+fn binding_key(value: &str) -> Option<&str> {
+    value.strip_prefix('{')?.strip_suffix('}')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct Progress {
     /// An optional title string.
-    title: Option<String>,
+    pub(crate) title: Option<String>,
     /// A status string that is displayed underneath the progress bar on the left. This string should reflect the status of the operation, like "Downloading..." or "Installing..."
-    status: String,
+    pub(crate) status: String,
     /// The value of the progress bar.
-    value: Value,
+    pub(crate) value: Value,
     /// An optional string to be displayed instead of the default percentage string. If this isn't provided, something like "70%" will be displayed.
-    value_string_override: Option<String>,
+    pub(crate) value_string_override: Option<String>,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
 }
 
 impl Progress {
+    /// The status, title and value string override can each be a `{key}` [binding] to the toast data instead of fixed text.
     pub fn new(status: &str, value: Value) -> Progress {
         Progress {
             title: None,
             status: status.into(),
             value,
             value_string_override: None,
+            extra: Extra::default(),
         }
     }
 
+    /// A progress bar with every part bound to the toast data, using the keys
+    /// `progressTitle`, `progressValue`, `progressValueString` and `progressStatus`.
+    ///
+    /// The values are set with [`Toast::data`] and changed with [`ToastHandle::update`](crate::identity::ToastHandle::update).
+    pub fn bound() -> Progress {
+        Progress::new(
+            &binding("progressStatus"),
+            Value::Binding("progressValue".into()),
+        )
+        .title(&binding("progressTitle"))
+        .value_string_override(&binding("progressValueString"))
+    }
+
     /// An optional title string.
     pub fn title(mut self, title: &str) -> Self {
         self.title = Some(title.into());
@@ -42,46 +95,126 @@ impl Progress {
         self.value_string_override = Some(value_string_override.into());
         self
     }
+
+    /// The title shown above the progress bar.
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The status shown underneath the progress bar.
+    pub fn get_status(&self) -> &str {
+        &self.status
+    }
+
+    /// The value of the progress bar.
+    pub fn get_value(&self) -> &Value {
+        &self.value
+    }
+
+    /// The string shown instead of the default percentage.
+    pub fn get_value_string_override(&self) -> Option<&str> {
+        self.value_string_override.as_deref()
+    }
+
+    /// Anything that was not understood when parsing.
+    pub fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 impl ToXML for Progress {
-    fn into_raw(
-        self,
-        doc: &windows::Data::Xml::Dom::XmlDocument,
-    ) -> Result<windows::Data::Xml::Dom::XmlElement> {
-        let progress_node = doc.CreateElement(&"progress".into())?;
-
-        if let Some(title) = self.title {
-            progress_node.SetAttribute(&"title".into(), &title.into())?;
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut progress_node = BytesStart::new("progress");
+
+        if let Some(title) = &self.title {
+            progress_node.push_attribute(("title", title.as_str()));
         }
 
-        progress_node.SetAttribute(&"status".into(), &self.status.into())?;
+        progress_node.push_attribute(("status", self.status.as_str()));
 
-        match self.value {
+        match &self.value {
             Value::Floating(value) => {
-                progress_node.SetAttribute(&"value".into(), &value.to_string().into())?;
+                progress_node.push_attribute(("value", value.to_string().as_str()));
             }
             Value::Indeterminate => {
-                progress_node.SetAttribute(&"value".into(), &"indeterminate".into())?;
+                progress_node.push_attribute(("value", "indeterminate"));
+            }
+            Value::Binding(key) => {
+                progress_node.push_attribute(("value", binding(key).as_str()));
             }
         }
 
-        if let Some(value_string_override) = self.value_string_override {
-            progress_node
-                .SetAttribute(&"valueStringOverride".into(), &value_string_override.into())?;
+        if let Some(value_string_override) = &self.value_string_override {
+            progress_node.push_attribute(("valueStringOverride", value_string_override.as_str()));
         }
 
-        Ok(progress_node)
+        self.extra.push_attributes(&mut progress_node);
+        write_empty(writer, progress_node, &self.extra)
     }
 }
 
+impl FromXML for Progress {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
+        }
+        let mut attributes = Attributes::new(element);
+        let value = attributes.take_with("value", |value| match value {
+            "indeterminate" => Some(Value::Indeterminate),
+            value => match binding_key(value) {
+                Some(key) => Some(Value::Binding(key.into())),
+                None => value.parse().ok().map(Value::Floating),
+            },
+        })?;
+        let status = attributes.take("status")?;
+        let title = attributes.take("title");
+        let value_string_override = attributes.take("valueStringOverride");
+        Some(Progress {
+            title,
+            status,
+            value,
+            value_string_override,
+            extra: attributes.into_extra(element.elements().cloned().collect()),
+        })
+    }
+}
 
-impl Toast {
-    pub fn add_progress(&self, progress: Progress) -> Result<()> {
-        let progress_node = progress.into_raw(&self.doc)?;
-        self.doc
-            .SelectSingleNode(&"/toast/visual/binding".into())?
-            .AppendChild(&progress_node)?;
+impl<S, C> Toast<S, C> {
+    pub fn add_progress(&mut self, progress: Progress) -> Result<()> {
+        self.visual
+            .binding
+            .children
+            .push(BindingChild::Progress(progress));
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::progress::{Progress, Value};
+    use crate::utils::into_raw::round_trip;
+
+    #[test]
+    fn writes_progress() {
+        let progress = Progress::new("Downloading...", Value::Floating(0.6))
+            .title("Weekly playlist")
+            .value_string_override("15/26 songs");
+        let (xml, parsed) = round_trip(&progress);
+        assert_eq!(
+            xml,
+            r#"<progress title="Weekly playlist" status="Downloading..." value="0.6" valueStringOverride="15/26 songs"/>"#
+        );
+        assert_eq!(parsed, progress);
+
+        let (xml, parsed) = round_trip(&Progress::new("Waiting", Value::Indeterminate));
+        assert_eq!(xml, r#"<progress status="Waiting" value="indeterminate"/>"#);
+        assert_eq!(parsed.get_value(), &Value::Indeterminate);
+
+        let (xml, parsed) = round_trip(&Progress::bound());
+        assert_eq!(
+            xml,
+            r#"<progress title="{progressTitle}" status="{progressStatus}" value="{progressValue}" valueStringOverride="{progressValueString}"/>"#
+        );
+        assert_eq!(parsed, Progress::bound());
+    }
+}
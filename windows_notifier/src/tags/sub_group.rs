@@ -1,43 +1,228 @@
-use crate::error::Result;
-use crate::utils::into_raw::ToXML;
+use std::io::Write;
+
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
+
+use crate::error::{Error, Result};
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_children, ToXML};
 use crate::Toast;
 
+use super::binding::BindingChild;
 use super::image::Image;
 use super::text::Text;
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum Child {
     Text(Text),
     Image(Image),
 }
 
-impl Toast {
-    /// Specifies vertical columns that can contain text and images.
-    pub fn add_sub_group(&self, children: Vec<Child>) -> Result<()> {
-        if let Err(_) = self
-            .doc
-            .SelectSingleNode(&"/toast/visual/binding/group".into())
-        {
-            self.doc
-                .SelectSingleNode(&"/toast/visual/binding".into())?
-                .AppendChild(&self.doc.CreateElement(&"group".into())?)?;
-        }
+/// A vertical column inside a [`Group`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct SubGroup {
+    pub(crate) children: Vec<Child>,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
+}
 
-        let sub_group = self.doc.CreateElement(&"subgroup".into())?;
+impl SubGroup {
+    /// The text and images in the column.
+    pub fn children(&self) -> &[Child] {
+        &self.children
+    }
+}
 
-        for child in children {
-            match child {
-                Child::Text(text) => {
-                    sub_group.AppendChild(&text.into_raw(&self.doc)?)?;
+impl ToXML for SubGroup {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut sub_group = BytesStart::new("subgroup");
+        self.extra.push_attributes(&mut sub_group);
+        write_children(writer, sub_group, |writer| {
+            for child in &self.children {
+                match child {
+                    Child::Text(text) => text.write_xml(writer)?,
+                    Child::Image(image) => image.write_xml(writer)?,
                 }
-                Child::Image(image) => {
-                    sub_group.AppendChild(&image.into_raw(&self.doc)?)?;
+            }
+            self.extra.write_elements(writer)
+        })
+    }
+}
+
+impl FromXML for SubGroup {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
+        }
+        let mut children = Vec::new();
+        let mut unknown = Vec::new();
+        for child in element.elements() {
+            match child.name.as_str() {
+                "text" => match Text::from_raw(child) {
+                    Some(text) => children.push(Child::Text(text)),
+                    None => unknown.push(child.clone()),
+                },
+                "image" => match Image::from_raw(child) {
+                    Some(image) => children.push(Child::Image(image)),
+                    None => unknown.push(child.clone()),
+                },
+                _ => unknown.push(child.clone()),
+            }
+        }
+        Some(SubGroup {
+            children,
+            extra: Attributes::new(element).into_extra(unknown),
+        })
+    }
+}
+
+/// Semantically identifies that the content in the group must either be displayed as a whole, or not displayed if it cannot fit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct Group {
+    pub(crate) sub_groups: Vec<SubGroup>,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
+}
+
+impl Group {
+    /// The columns of the group.
+    pub fn sub_groups(&self) -> &[SubGroup] {
+        &self.sub_groups
+    }
+}
+
+impl ToXML for Group {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut group = BytesStart::new("group");
+        self.extra.push_attributes(&mut group);
+        write_children(writer, group, |writer| {
+            for sub_group in &self.sub_groups {
+                sub_group.write_xml(writer)?;
+            }
+            self.extra.write_elements(writer)
+        })
+    }
+}
+
+impl FromXML for Group {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
+        }
+        let mut sub_groups = Vec::new();
+        let mut unknown = Vec::new();
+        for child in element.elements() {
+            match (child.name.as_str(), SubGroup::from_raw(child)) {
+                ("subgroup", Some(sub_group)) => sub_groups.push(sub_group),
+                _ => unknown.push(child.clone()),
+            }
+        }
+        Some(Group {
+            sub_groups,
+            extra: Attributes::new(element).into_extra(unknown),
+        })
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// Specifies vertical columns that can contain text and images.
+    pub fn add_sub_group(&mut self, children: Vec<Child>) -> Result<()> {
+        for child in &children {
+            if let Child::Text(text) = child {
+                if text.id == Some(0) {
+                    return Err(Error::invalid_argument(
+                        "toast/visual/binding/group/subgroup/text/@id",
+                        "id 0 is reserved for the title",
+                    ));
                 }
             }
         }
 
-        self.doc
-            .SelectSingleNode(&"/toast/visual/binding/group".into())?
-            .AppendChild(&sub_group)?;
-        return Ok(());
+        let binding = &mut self.visual.binding;
+        let group = binding.children.iter_mut().find_map(|child| match child {
+            BindingChild::Group(group) => Some(group),
+            _ => None,
+        });
+        let sub_group = SubGroup {
+            children,
+            extra: Extra::default(),
+        };
+        match group {
+            Some(group) => group.sub_groups.push(sub_group),
+            None => binding.children.push(BindingChild::Group(Group {
+                sub_groups: vec![sub_group],
+                extra: Extra::default(),
+            })),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::binding::BindingChild;
+    use crate::tags::image::{Image, Placement};
+    use crate::tags::sub_group::Child;
+    use crate::tags::text::Text;
+    use crate::utils::into_raw::round_trip;
+    use crate::Toast;
+
+    #[test]
+    fn writes_groups() {
+        let mut toast = Toast::new().unwrap();
+        toast
+            .add_sub_group(vec![
+                Child::Text(Text::new("52°").id(1)),
+                Child::Image(Image::new("sun.png").set_placement(Placement::Inline)),
+            ])
+            .unwrap();
+        toast
+            .add_sub_group(vec![Child::Text(Text::new("Sunny").id(2))])
+            .unwrap();
+        assert!(toast
+            .add_sub_group(vec![Child::Text(Text::new("Title").id(0))])
+            .is_err());
+
+        let groups: Vec<_> = toast
+            .binding_children()
+            .iter()
+            .filter_map(|child| match child {
+                BindingChild::Group(group) => Some(group),
+                _ => None,
+            })
+            .collect();
+        let [group] = groups[..] else {
+            panic!("both columns are in one group");
+        };
+        let (xml, parsed) = round_trip(group);
+        assert_eq!(
+            xml,
+            r#"<group><subgroup><text id="1">52°</text><image src="sun.png"/></subgroup><subgroup><text id="2">Sunny</text></subgroup></group>"#
+        );
+        assert_eq!(&parsed, group);
+        assert_eq!(parsed.sub_groups()[1].children().len(), 1);
     }
 }
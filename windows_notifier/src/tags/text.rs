@@ -1,24 +1,48 @@
-use crate::error::{Error, Result, XmlErr};
+use std::io::Write;
 
-use crate::utils::into_raw::ToXML;
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
+
+use crate::error::{Error, Result};
+
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_text, ToXML};
+use crate::validate::MAX_TEXTS;
 use crate::Toast;
 
+use super::binding::BindingChild;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct Text {
     /// The text element in the toast template that this text is intended for.
     /// If a template has only one text element, then this value is 1.
     /// The number of available text positions is based on the template definition.
-    id: Option<i32>,
+    pub(crate) id: Option<i32>,
     /// The text to display.
-    text: String,
+    pub(crate) text: String,
+    /// The message id the text is [localized](crate::localize) from, not part of the xml.
+    pub(crate) message: Option<String>,
     /// the text is always displayed at the bottom of your notification, along with your app's identity or the notification's timestamp.
     ///
     /// only one will will be shown at a time.
-    bottem_text: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) bottem_text: bool,
     /// Set to "true" to center the text for incoming call notifications.
     /// This value is only used for notifications with with a scenario value of "incomingCall";
     /// otherwise, it is ignored. For more information, see Toast content.
-    hint_call_scenario_center_align: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) hint_call_scenario_center_align: bool,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
 }
 
 impl Text {
@@ -27,11 +51,27 @@ impl Text {
         Text {
             id: None,
             text: text.into(),
+            message: None,
             bottem_text: false,
             hint_call_scenario_center_align: false,
+            extra: Extra::default(),
+        }
+    }
+
+    /// The title text, which always uses id 0.
+    pub(crate) fn title(text: impl Into<String>) -> Text {
+        Text {
+            id: Some(0),
+            ..Text::new(text)
         }
     }
 
+    /// Replace the text with message `id` when the toast is [localized](crate::Toast::localize).
+    pub fn message(mut self, id: impl Into<String>) -> Self {
+        self.message = Some(id.into());
+        self
+    }
+
     /// Set the id of the text element.
     pub fn id(mut self, id: i32) -> Self {
         self.id = Some(id);
@@ -52,49 +92,154 @@ impl Text {
         self.hint_call_scenario_center_align = true;
         self
     }
+
+    /// The id of the text element.
+    pub fn get_id(&self) -> Option<i32> {
+        self.id
+    }
+
+    /// The text to display.
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    /// The message id the text is localized from.
+    pub fn get_message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Whether the text is shown at the bottom of the notification.
+    pub fn is_bottem_text(&self) -> bool {
+        self.bottem_text
+    }
+
+    /// Anything that was not understood when parsing.
+    pub fn extra(&self) -> &Extra {
+        &self.extra
+    }
 }
 
 impl ToXML for Text {
-    fn into_raw(
-        self,
-        doc: &windows::Data::Xml::Dom::XmlDocument,
-    ) -> Result<windows::Data::Xml::Dom::XmlElement> {
-        if self.id == Some(0) {
-            return Err(Error::XmlErr(XmlErr::InvatedArg(
-                "id 0 is reserved for the title".into(),
-            )));
-        }
-        let text_node = doc.CreateElement(&"text".into()).unwrap();
-        text_node.SetInnerText(&self.text.into()).unwrap();
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut text_node = BytesStart::new("text");
         if let Some(id) = self.id {
-            text_node.SetAttribute(&"id".into(), &id.to_string().into())?;
+            text_node.push_attribute(("id", id.to_string().as_str()));
         }
         if self.bottem_text {
-            text_node.SetAttribute(&"placement".into(), &"attribution".into())?;
+            text_node.push_attribute(("placement", "attribution"));
         }
         if self.hint_call_scenario_center_align {
-            text_node.SetAttribute(&"hint-callScenarioCenterAlign".into(), &"true".into())?;
+            text_node.push_attribute(("hint-callScenarioCenterAlign", "true"));
         }
-        Ok(text_node)
+        self.extra.push_attributes(&mut text_node);
+        write_text(writer, text_node, &self.text, &self.extra)
+    }
+}
+
+impl FromXML for Text {
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        let mut attributes = Attributes::new(element);
+        let id = attributes.take_with("id", |id| id.parse().ok());
+        let bottem_text = attributes
+            .take_with("placement", |placement| {
+                (placement == "attribution").then_some(true)
+            })
+            .unwrap_or(false);
+        let hint_call_scenario_center_align = attributes.take_bool("hint-callScenarioCenterAlign");
+        let extra = attributes.into_extra(element.elements().cloned().collect());
+        Some(Text {
+            id,
+            text: element.text(),
+            message: None,
+            bottem_text,
+            hint_call_scenario_center_align,
+            extra,
+        })
     }
 }
 
-impl<S>  Toast<S> {
+impl<S, C> Toast<S, C> {
     /// Set the title of the toast notification.
     pub fn title(&mut self, title: &str) -> Result<()> {
-        self.doc
-            .SelectSingleNode(&"//*[@id='0']".into())?
-            .SetInnerText(&title.into())?;
+        self.title_text().text = title.into();
         Ok(())
     }
 
+    /// Replace the title with message `id` when the toast is [localized](crate::Toast::localize).
+    pub fn title_message(&mut self, id: &str) -> Result<()> {
+        self.title_text().message = Some(id.into());
+        Ok(())
+    }
+
+    /// The title text, added empty if the toast has none yet.
+    fn title_text(&mut self) -> &mut Text {
+        let children = &mut self.visual.binding.children;
+        // Parsed toasts may not use id 0, in which case the first text is the title.
+        let index = children
+            .iter()
+            .position(|child| matches!(child, BindingChild::Text(text) if text.id == Some(0)))
+            .or_else(|| {
+                children
+                    .iter()
+                    .position(|child| matches!(child, BindingChild::Text(_)))
+            });
+        let index = index.unwrap_or_else(|| {
+            children.insert(0, BindingChild::Text(Text::title("")));
+            0
+        });
+        match &mut children[index] {
+            BindingChild::Text(text) => text,
+            _ => unreachable!("the title is a text"),
+        }
+    }
+
     /// Set the text of the toast notification.
     /// If the id already exists, it will be overwritten.
     /// id 0 is reserved for the title.
     pub fn add_text(&mut self, text: Text) -> Result<()> {
-        self.doc
-            .SelectSingleNode(&"/toast/visual/binding".into())?
-            .AppendChild(&text.into_raw(&self.doc)?)?;
-        return Ok(());
+        if text.id == Some(0) {
+            return Err(Error::invalid_argument(
+                "toast/visual/binding/text/@id",
+                "id 0 is reserved for the title",
+            ));
+        }
+        let children = &self.visual.binding.children;
+        let texts = children
+            .iter()
+            .filter(|child| matches!(child, BindingChild::Text(text) if !text.bottem_text))
+            .count();
+        if !text.bottem_text && texts >= MAX_TEXTS {
+            return Err(Error::limit_exceeded(
+                format!("toast/visual/binding/text[{}]", texts + 1),
+                MAX_TEXTS,
+            ));
+        }
+        self.visual.binding.children.push(BindingChild::Text(text));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::text::Text;
+    use crate::utils::into_raw::round_trip;
+
+    #[test]
+    fn writes_text() {
+        let text = Text::new("Jill & Bill").id(1).bottem_text();
+        let (xml, parsed) = round_trip(&text);
+        assert_eq!(
+            xml,
+            r#"<text id="1" placement="attribution">Jill &amp; Bill</text>"#
+        );
+        assert_eq!(parsed, text);
+
+        let text = Text::new("Incoming").hint_call_scenario_center_align();
+        let (xml, parsed) = round_trip(&text);
+        assert_eq!(
+            xml,
+            r#"<text hint-callScenarioCenterAlign="true">Incoming</text>"#
+        );
+        assert_eq!(parsed, text);
     }
 }
@@ -1,8 +1,21 @@
-use crate::error::Result;
+use std::fmt;
+use std::time::SystemTime;
+
+use crate::error::{Error, Result};
+use crate::raw::Extra;
 use crate::Toast;
 
+use super::action::Action;
+use super::actions::Actions;
+use super::activation::{protocol_uri, ActivationType};
+
 /// Duraction of the toast notification.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum Duration {
     /// 7 seconds
     Short,
@@ -12,7 +25,12 @@ pub enum Duration {
 }
 
 /// The scenario your toast is used for, like an alarm or reminder.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum Scenarios {
     ///  reminder notification.
     /// This will be displayed pre-expanded and stay on the user's screen till dismissed.
@@ -32,29 +50,198 @@ pub enum Scenarios {
     Urgent,
 }
 
+impl Duration {
+    pub(crate) fn from_attr(value: &str) -> Option<Duration> {
+        match value {
+            "short" => Some(Duration::Short),
+            "long" => Some(Duration::Long),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Duration::Short => write!(f, "short"),
+            Duration::Long => write!(f, "long"),
+        }
+    }
+}
+
 impl Scenarios {
-    /// Converts the scenario to a string.
-    pub fn to_string(&self) -> String {
+    pub(crate) fn from_attr(value: &str) -> Option<Scenarios> {
+        match value {
+            "reminder" => Some(Scenarios::Reminder),
+            "alarm" => Some(Scenarios::Alarm),
+            "incomingCall" => Some(Scenarios::IncomingCall),
+            "urgent" => Some(Scenarios::Urgent),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Scenarios {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Scenarios::Reminder => "reminder".into(),
-            Scenarios::Alarm => "alarm".into(),
-            Scenarios::IncomingCall => "incomingCall".into(),
-            Scenarios::Urgent => "urgent".into(),
+            Scenarios::Reminder => write!(f, "reminder"),
+            Scenarios::Alarm => write!(f, "alarm"),
+            Scenarios::IncomingCall => write!(f, "incomingCall"),
+            Scenarios::Urgent => write!(f, "urgent"),
         }
     }
 }
 
-impl Toast {
-    /// Set the duration of the toast notification
-    pub fn duration(&self, duration: Duration) -> Result<()> {
-        let duration = match duration {
-            Duration::Short => "short",
-            Duration::Long => "long",
-        };
-        self.doc
-            .DocumentElement()?
-            .SetAttribute(&"duration".into(), &duration.into())?;
+impl<S, C> Toast<S, C> {
+    /// The duration of the toast notification.
+    pub fn get_duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// The string passed to the application when it is activated by the toast.
+    pub fn get_launch(&self) -> Option<&str> {
+        self.launch.as_deref()
+    }
+
+    /// The type of activation used when the user clicks the toast.
+    pub fn get_activation_type(&self) -> Option<ActivationType> {
+        self.activation_type
+    }
+
+    /// The package family name of the app that handles the protocol activation of the toast.
+    pub fn get_target_application_pfn(&self) -> Option<&str> {
+        self.target_application_pfn.as_deref()
+    }
+
+    /// The scenario your toast is used for.
+    pub fn get_scenario(&self) -> Option<Scenarios> {
+        self.scenario
+    }
+
+    /// The time shown on the toast instead of the time it was delivered.
+    pub fn get_display_timestamp(&self) -> Option<SystemTime> {
+        self.display_timestamp
+    }
+
+    /// When the toast is removed from the Action Center.
+    pub fn get_expiration(&self) -> Option<SystemTime> {
+        self.expiration
+    }
+
+    /// Whether the toast is removed from the Action Center when the system restarts.
+    pub fn is_expires_on_reboot(&self) -> bool {
+        self.expires_on_reboot
+    }
+
+    /// Anything on the toast element that was not understood when parsing.
+    pub fn extra(&self) -> &Extra {
+        &self.extra
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A scenario the toast can be put in with [`Toast::scenario`], carrying the rules of that scenario in the type.
+pub trait Scenario: sealed::Sealed {
+    /// Adds anything the scenario needs to the toast, returning the scenario to write.
+    #[doc(hidden)]
+    fn apply(self, actions: &mut Actions) -> Result<Scenarios>;
+}
+
+/// A scenario that can play [looping](super::audio::Audio::loop_) audio without a long duration.
+pub trait LoopingScenario: Scenario {}
+
+/// A reminder notification, see [`Scenarios::Reminder`].
+///
+/// Windows only shows a reminder as one if a button activates in the background, so a reminder is always made with such an action.
+pub struct Reminder {
+    action: Action,
+}
 
+impl Reminder {
+    /// A reminder with `action` as its background activated button.
+    /// The activation type of `action` is set to [`ActivationType::Background`].
+    pub fn new(action: Action) -> Reminder {
+        Reminder {
+            action: action.activation_type(ActivationType::Background),
+        }
+    }
+}
+
+/// An alarm notification, see [`Scenarios::Alarm`].
+pub struct Alarm;
+
+/// An incoming call notification, see [`Scenarios::IncomingCall`].
+pub struct IncomingCall;
+
+/// An important notification, see [`Scenarios::Urgent`].
+pub struct Urgent;
+
+impl sealed::Sealed for Reminder {}
+impl sealed::Sealed for Alarm {}
+impl sealed::Sealed for IncomingCall {}
+impl sealed::Sealed for Urgent {}
+
+impl Scenario for Reminder {
+    fn apply(self, actions: &mut Actions) -> Result<Scenarios> {
+        actions.push_action(self.action)?;
+        Ok(Scenarios::Reminder)
+    }
+}
+
+impl Scenario for Alarm {
+    fn apply(self, _actions: &mut Actions) -> Result<Scenarios> {
+        Ok(Scenarios::Alarm)
+    }
+}
+
+impl Scenario for IncomingCall {
+    fn apply(self, _actions: &mut Actions) -> Result<Scenarios> {
+        Ok(Scenarios::IncomingCall)
+    }
+}
+
+impl Scenario for Urgent {
+    fn apply(self, _actions: &mut Actions) -> Result<Scenarios> {
+        Ok(Scenarios::Urgent)
+    }
+}
+
+impl LoopingScenario for Alarm {}
+impl LoopingScenario for IncomingCall {}
+
+impl<S> Toast<S, ()> {
+    /// The scenario your toast is used for, like an alarm or reminder.
+    ///
+    /// The scenario becomes part of the toast type, so the rules of the scenario are checked when compiling,
+    /// like only allowing [alarm commands](super::commands::AlarmCommands) on an [`Alarm`].
+    ///
+    /// Returns [`Error::LimitExceeded`] if the toast has no room left for the action of a [`Reminder`].
+    pub fn scenario<N: Scenario>(mut self, scenario: N) -> Result<Toast<S, N>> {
+        self.scenario = Some(scenario.apply(&mut self.actions)?);
+        Ok(self.into_state())
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// Set the duration of the toast notification
+    ///
+    /// Looping audio outside of an alarm or incoming call needs a long duration, so it can not be made short.
+    pub fn duration(&mut self, duration: Duration) -> Result<()> {
+        let looping = self.audio.as_ref().is_some_and(|audio| audio.loop_);
+        let looping_scenario = matches!(
+            self.scenario,
+            Some(Scenarios::Alarm) | Some(Scenarios::IncomingCall)
+        );
+        if duration == Duration::Short && looping && !looping_scenario {
+            return Err(Error::invalid_argument(
+                "toast/@duration",
+                "looping audio needs a long duration",
+            ));
+        }
+        self.duration = Some(duration);
         Ok(())
     }
 
@@ -62,30 +249,68 @@ impl Toast {
     /// The format and contents of this string are defined by the app for its own use.
     /// When the user taps or clicks the toast to launch its associated app,
     /// the launch string provides the context to the app that allows it to show the user a view relevant to the toast content, rather than launching in its default way.
-    pub fn launch(&self, launch: &str) -> Result<()> {
-        self.doc
-            .DocumentElement()?
-            .SetAttribute(&"launch".into(), &launch.into())?;
+    /// It can be a plain string or [`ToastArguments`](crate::arguments::ToastArguments).
+    pub fn launch(&mut self, launch: impl Into<String>) -> Result<()> {
+        self.launch = Some(launch.into());
+        Ok(())
+    }
 
+    /// The type of activation used when the user clicks the toast.
+    ///
+    /// Returns [`Error::InvalidArgument`] for [`ActivationType::System`], which only actions support.
+    pub fn activation_type(&mut self, activation_type: ActivationType) -> Result<()> {
+        if activation_type == ActivationType::System {
+            return Err(Error::invalid_argument(
+                "toast",
+                "only actions can use system activation",
+            ));
+        }
+        self.activation_type = Some(activation_type);
         Ok(())
     }
 
-    /// The scenario your toast is used for, like an alarm or reminder.
-    pub fn scenario(&self, scenario: Scenarios) -> Result<()> {
-        self.doc
-            .DocumentElement()?
-            .SetAttribute(&"scenario".into(), &scenario.to_string().into())?;
+    /// Launch `uri` with protocol activation when the user clicks the toast.
+    ///
+    /// Returns [`Error::InvalidArgument`] if `uri` is not an absolute URI.
+    pub fn protocol_launch(&mut self, uri: &str) -> Result<()> {
+        self.launch = Some(protocol_uri("toast", uri)?);
+        self.activation_type = Some(ActivationType::Protocol);
+        Ok(())
+    }
 
+    /// The package family name of the app that handles the protocol activation of the toast.
+    pub fn target_application_pfn(&mut self, pfn: impl Into<String>) -> Result<()> {
+        self.target_application_pfn = Some(pfn.into());
         Ok(())
     }
 
     /// Specifies whether styled buttons should be used.
     /// The styling of the button is determined by the **hint-buttonStyle** attribute of the [action](element-action.md) element.
-    pub fn styled_button(&self, styled_button: bool) -> Result<()> {
-        self.doc
-            .DocumentElement()?
-            .SetAttribute(&"useButtonStyle".into(), &styled_button.to_string().into())?;
+    pub fn styled_button(&mut self, styled_button: bool) -> Result<()> {
+        self.use_button_style = styled_button;
+        Ok(())
+    }
+
+    /// Overrides the default timestamp with one that represents when your notification content was actually delivered,
+    /// rather than the time the notification was received by the Windows platform.
+    /// Written in ISO 8601 as `displayTimestamp`, like `2017-04-15T19:45:00Z`.
+    ///
+    /// The `chrono` and `time` date types convert into a [`SystemTime`].
+    pub fn display_timestamp(&mut self, timestamp: impl Into<SystemTime>) -> Result<()> {
+        self.display_timestamp = Some(timestamp.into());
+        Ok(())
+    }
+
+    /// Remove the toast from the Action Center at `expiration`, like a meeting reminder after the meeting.
+    /// This is not part of the xml, it is given to the backend when the toast is shown.
+    pub fn expiration(&mut self, expiration: impl Into<SystemTime>) -> Result<()> {
+        self.expiration = Some(expiration.into());
+        Ok(())
+    }
 
+    /// Remove the toast from the Action Center when the system restarts.
+    pub fn expires_on_reboot(&mut self, expires_on_reboot: bool) -> Result<()> {
+        self.expires_on_reboot = expires_on_reboot;
         Ok(())
     }
 }
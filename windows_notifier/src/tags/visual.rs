@@ -1,14 +1,116 @@
-use windows::Data::Xml::Dom::XmlElement;
+use std::io::Write;
+
+use quick_xml::events::BytesStart;
+use quick_xml::Writer;
 
 use crate::error::Result;
+use crate::raw::{Attributes, Extra, FromXML, RawElement};
+use crate::utils::into_raw::{write_children, ToXML};
 use crate::Toast;
 
-impl Toast {
-    fn visual_get_element(&self) -> Result<XmlElement> {
-        let res = self.doc.GetElementById(&"visual".into())?;
-        Ok(res)
+use super::binding::Binding;
+
+/// The `visual` element of the toast, holding the binding with the visible content.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
+pub struct Visual {
+    /// The target locale of the toast, like "en-US" or "fr-FR", used for the localized images and text.
+    pub(crate) lang: Option<String>,
+    /// Set to "true" to allow Windows to append a query string to the image URI supplied in the toast notification.
+    pub(crate) add_image_query: bool,
+    /// A default base URI that is combined with relative URIs in image source attributes.
+    pub(crate) base_uri: Option<String>,
+    /// The binding that holds the text, images and other content of the toast.
+    pub(crate) binding: Binding,
+    /// Anything that was not understood when parsing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Extra::is_empty")
+    )]
+    pub(crate) extra: Extra,
+}
+
+impl Default for Visual {
+    fn default() -> Self {
+        Visual::new()
+    }
+}
+
+impl Visual {
+    pub(crate) fn new() -> Visual {
+        Visual {
+            lang: None,
+            add_image_query: false,
+            base_uri: None,
+            binding: Binding::new(),
+            extra: Extra::default(),
+        }
+    }
+
+    /// Anything that was not understood when parsing.
+    pub fn extra(&self) -> &Extra {
+        &self.extra
+    }
+}
+
+impl ToXML for Visual {
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut visual = BytesStart::new("visual");
+        if let Some(lang) = &self.lang {
+            visual.push_attribute(("lang", lang.as_str()));
+        }
+        if self.add_image_query {
+            visual.push_attribute(("addImageQuery", "true"));
+        }
+        if let Some(base_uri) = &self.base_uri {
+            visual.push_attribute(("baseUri", base_uri.as_str()));
+        }
+        self.extra.push_attributes(&mut visual);
+        write_children(writer, visual, |writer| {
+            self.binding.write_xml(writer)?;
+            self.extra.write_elements(writer)
+        })
     }
+}
 
+impl FromXML for Visual {
+    /// The first binding becomes the typed binding, any other element is kept raw.
+    fn from_raw(element: &RawElement) -> Option<Self> {
+        if element.has_text() {
+            return None;
+        }
+        let mut attributes = Attributes::new(element);
+        let lang = attributes.take("lang");
+        let add_image_query = attributes.take_bool("addImageQuery");
+        let base_uri = attributes.take("baseUri");
+
+        let mut binding = None;
+        let mut unknown = Vec::new();
+        for child in element.elements() {
+            if binding.is_none() && child.name == "binding" {
+                binding = Binding::from_raw(child);
+                if binding.is_some() {
+                    continue;
+                }
+            }
+            unknown.push(child.clone());
+        }
+
+        Some(Visual {
+            lang,
+            add_image_query,
+            base_uri,
+            binding: binding?,
+            extra: attributes.into_extra(unknown),
+        })
+    }
+}
+
+impl<S, C> Toast<S, C> {
     /// Set to "true" to allow Windows to append a query string to the image URI supplied in the toast notification. Use this attribute if your server hosts images and can handle query strings, either by retrieving an image variant based on the query strings or by ignoring the query string and returning the image as specified without the query string. This query string specifies scale, contrast setting, and language; for instance, a value of
     ///
     /// "www.website.com/images/hello.png"
@@ -16,16 +118,20 @@ impl Toast {
     /// given in the notification becomes
     ///
     /// "www.website.com/images/hello.png?ms-scale=100&ms-contrast=standard&ms-lang=en-us"
-    pub fn visual_add_image_query(&self) -> Result<()> {
-        let element = self.visual_get_element()?;
-        element.SetAttribute(&"addImageQuery".into(), &"true".into())?;
+    pub fn visual_add_image_query(&mut self) -> Result<()> {
+        self.visual.add_image_query = true;
+        Ok(())
+    }
+
+    /// The target locale of the toast, like "en-US" or "fr-FR", set by [`Toast::localize`].
+    pub fn visual_lang(&mut self, lang: &str) -> Result<()> {
+        self.visual.lang = Some(lang.into());
         Ok(())
     }
 
     /// A default base URI that is combined with relative URIs in image source attributes.
-    pub fn visual_base_uri(&self, base_uri: &str) -> Result<()> {
-        let element = self.visual_get_element()?;
-        element.SetAttribute(&"baseUri".into(), &base_uri.into())?;
+    pub fn visual_base_uri(&mut self, base_uri: &str) -> Result<()> {
+        self.visual.base_uri = Some(base_uri.into());
         Ok(())
     }
 }
@@ -0,0 +1,270 @@
+//! Reusable toasts with `{{placeholder}}` markers, rendered with different values each time they are sent.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::time::SystemTime;
+
+use crate::backend::NotificationData;
+use crate::error::{Error, Result};
+use crate::identity::ToastIdentity;
+use crate::raw::{RawElement, RawNode};
+use crate::Toast;
+
+/// A toast with `{{placeholder}}` markers in its text and attribute values,
+/// like text content, action arguments or image sources.
+///
+/// The toast is built once with the normal builder, and each [render](ToastTemplate::render) only fills in the values.
+/// What is not part of the xml, like the identity, the data, the expiration and the message ids, is kept as it is.
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use windows_notifier::{tags::text::Text, template::ToastTemplate, Toast};
+/// let mut toast = Toast::new()?;
+/// toast.title("{{sender}} sent you a message")?;
+/// toast.add_text(Text::new("{{ message }}"))?;
+/// let template = ToastTemplate::new(&toast)?;
+///
+/// let values = HashMap::from([("sender", "Jill"), ("message", "See you at 5")]);
+/// let toast = template.render(&values)?;
+/// assert!(toast.into_raw()?.contains("Jill sent you a message"));
+/// # Ok::<(), windows_notifier::error::Error>(())
+/// ```
+pub struct ToastTemplate<S = (), C = ()> {
+    element: RawElement,
+    variables: Vec<String>,
+    identity: ToastIdentity,
+    data: Option<NotificationData>,
+    expiration: Option<SystemTime>,
+    expires_on_reboot: bool,
+    message_ids: Vec<Option<String>>,
+    phantom: PhantomData<(S, C)>,
+}
+
+impl<S, C> ToastTemplate<S, C> {
+    /// A template of `toast`, keeping its typestate.
+    pub fn new(toast: &Toast<S, C>) -> Result<ToastTemplate<S, C>> {
+        let element = RawElement::from_reader(toast.into_raw()?.as_bytes())?;
+        let mut variables = Vec::new();
+        render_element(&element, &|_| None, &mut variables);
+        Ok(ToastTemplate {
+            element,
+            variables,
+            identity: toast.identity.clone(),
+            data: toast.data.clone(),
+            expiration: toast.expiration,
+            expires_on_reboot: toast.expires_on_reboot,
+            message_ids: toast.message_ids(),
+            phantom: PhantomData,
+        })
+    }
+
+    /// The names of all placeholders in the template, in the order they first appear.
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    /// Render the toast, taking the value of each placeholder from `values`.
+    ///
+    /// Returns [`Error::MissingVariables`] with every placeholder that has no value.
+    pub fn render<K, V>(&self, values: &HashMap<K, V>) -> Result<Toast<S, C>>
+    where
+        K: Borrow<str> + Hash + Eq,
+        V: fmt::Display,
+    {
+        self.render_with(&|name| values.get(name).map(|value| value.to_string()))
+    }
+
+    /// Render the toast, taking the value of each placeholder from the fields of `value`.
+    ///
+    /// Nested fields are used with a dot, like `{{user.name}}`. Null fields count as missing.
+    #[cfg(feature = "serde")]
+    pub fn render_serialize<T: serde::Serialize>(&self, value: &T) -> Result<Toast<S, C>> {
+        let value = serde_json::to_value(value)
+            .map_err(|err| Error::invalid_argument("template", err.to_string()))?;
+        self.render_with(&|name| {
+            let field = name
+                .split('.')
+                .try_fold(&value, |value, key| value.get(key))?;
+            match field {
+                serde_json::Value::Null => None,
+                serde_json::Value::String(string) => Some(string.clone()),
+                other => Some(other.to_string()),
+            }
+        })
+    }
+
+    fn render_with(&self, lookup: &dyn Fn(&str) -> Option<String>) -> Result<Toast<S, C>> {
+        let mut missing = Vec::new();
+        let element = render_element(&self.element, lookup, &mut missing);
+        if !missing.is_empty() {
+            return Err(Error::MissingVariables(missing));
+        }
+        let mut toast = Toast::from_element(&element)?;
+        toast.identity = self.identity.clone();
+        toast.data = self.data.clone();
+        toast.expiration = self.expiration;
+        toast.expires_on_reboot = self.expires_on_reboot;
+        // The rendered xml has the same elements as the template, so the ids line up.
+        for (id, message_id) in toast.message_ids_mut().into_iter().zip(&self.message_ids) {
+            id.clone_from(message_id);
+        }
+        Ok(toast.into_state())
+    }
+}
+
+/// Fills in the placeholders of `element` and its children, adding the names `lookup` has no value for to `missing`.
+fn render_element(
+    element: &RawElement,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    missing: &mut Vec<String>,
+) -> RawElement {
+    RawElement {
+        name: element.name.clone(),
+        attributes: element
+            .attributes
+            .iter()
+            .map(|(key, value)| (key.clone(), render_str(value, lookup, missing)))
+            .collect(),
+        children: element
+            .children
+            .iter()
+            .map(|child| match child {
+                RawNode::Element(element) => {
+                    RawNode::Element(render_element(element, lookup, missing))
+                }
+                RawNode::Text(text) => RawNode::Text(render_str(text, lookup, missing)),
+            })
+            .collect(),
+    }
+}
+
+fn render_str(
+    value: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    missing: &mut Vec<String>,
+) -> String {
+    let mut rendered = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + 2 + end + 2];
+        let name = placeholder[2..placeholder.len() - 2].trim();
+        rendered.push_str(&rest[..start]);
+        match lookup(name) {
+            Some(value) => rendered.push_str(&value),
+            None => {
+                if !missing.iter().any(|m| m == name) {
+                    missing.push(name.into());
+                }
+                rendered.push_str(placeholder);
+            }
+        }
+        rest = &rest[start + placeholder.len()..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::backend::NotificationData;
+    use crate::error::Error;
+    use crate::identity::ToastIdentity;
+    use crate::tags::action::Action;
+    use crate::tags::image::Image;
+    use crate::tags::text::Text;
+    use crate::template::ToastTemplate;
+    use crate::Toast;
+
+    #[test]
+    fn renders_placeholders() {
+        let mut toast = Toast::new().unwrap();
+        toast.title("Build {{ build }} finished").unwrap();
+        toast
+            .add_image(Image::new("https://ci.example.com/{{project}}.png"))
+            .unwrap();
+        toast
+            .add_action(Action::new("Open".into(), "action=open&build={{build}}"))
+            .unwrap();
+        let template = ToastTemplate::new(&toast).unwrap();
+        assert_eq!(template.variables(), ["build", "project"]);
+
+        let values = HashMap::from([("build", "42".to_string()), ("project", "a&b".into())]);
+        let raw = template.render(&values).unwrap().into_raw().unwrap();
+        assert!(raw.contains("Build 42 finished"));
+        assert!(raw.contains(r#"src="https://ci.example.com/a&amp;b.png""#));
+        assert!(raw.contains(r#"arguments="action=open&amp;build=42""#));
+
+        let values = HashMap::from([("project", 1)]);
+        let Err(Error::MissingVariables(missing)) = template.render(&values) else {
+            panic!("build is missing");
+        };
+        assert_eq!(missing, ["build"]);
+    }
+
+    #[test]
+    fn keeps_what_is_not_in_the_xml() {
+        let expiration = UNIX_EPOCH + Duration::from_secs(1_900_000_000);
+        let mut toast = Toast::new().unwrap();
+        toast
+            .identity(ToastIdentity::new("build-42").unwrap().group("ci").unwrap())
+            .unwrap();
+        toast
+            .data(NotificationData::new().value("progress", "0.5"))
+            .unwrap();
+        toast.expiration(expiration).unwrap();
+        toast.expires_on_reboot(true).unwrap();
+        toast
+            .add_text(Text::new("{{status}}").message("status"))
+            .unwrap();
+        let template = ToastTemplate::new(&toast).unwrap();
+
+        let toast = template
+            .render(&HashMap::from([("status", "Passed")]))
+            .unwrap();
+        assert_eq!(toast.get_identity().get_tag(), Some("build-42"));
+        assert_eq!(toast.get_identity().get_group(), Some("ci"));
+        assert_eq!(toast.get_data().unwrap().get_value("progress"), Some("0.5"));
+        assert_eq!(toast.get_expiration(), Some(expiration));
+        assert!(toast.is_expires_on_reboot());
+        let ids = toast.message_ids();
+        assert!(ids.contains(&Some("status".into())));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn renders_serialize() {
+        #[derive(serde::Serialize)]
+        struct Message<'a> {
+            sender: &'a str,
+            unread: u32,
+        }
+
+        let mut toast = Toast::new().unwrap();
+        toast
+            .title("{{message.sender}}: {{message.unread}} unread")
+            .unwrap();
+        let template = ToastTemplate::new(&toast).unwrap();
+        let values = HashMap::from([(
+            "message",
+            Message {
+                sender: "Jill",
+                unread: 3,
+            },
+        )]);
+        let raw = template
+            .render_serialize(&values)
+            .unwrap()
+            .into_raw()
+            .unwrap();
+        assert!(raw.contains("Jill: 3 unread"));
+    }
+}
@@ -0,0 +1,95 @@
+//! Awaiting blocking work from any async runtime, without depending on one.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+/// The result of the work, the task waiting for it and whether the [`Completer`] is gone.
+type Shared<T> = Arc<Mutex<Handoff<T>>>;
+
+struct Handoff<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+    completer_dropped: bool,
+}
+
+/// The [`Completer`] of a [`Blocking`] was dropped without completing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Canceled;
+
+/// Runs `work` on its own thread, the returned future completes with its result,
+/// or with [`Canceled`] if `work` panicked.
+pub(crate) fn spawn_blocking<T, F>(work: F) -> Blocking<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (result, blocking) = oneshot();
+    thread::spawn(move || result.complete(work()));
+    blocking
+}
+
+/// A future that completes once another thread hands it its output.
+pub(crate) fn oneshot<T>() -> (Completer<T>, Blocking<T>) {
+    let shared: Shared<T> = Arc::new(Mutex::new(Handoff {
+        output: None,
+        waker: None,
+        completer_dropped: false,
+    }));
+    (
+        Completer {
+            shared: shared.clone(),
+        },
+        Blocking { shared },
+    )
+}
+
+/// Completes the future of [`oneshot`], dropping it without completing completes the future with [`Canceled`].
+pub(crate) struct Completer<T> {
+    shared: Shared<T>,
+}
+
+impl<T> Completer<T> {
+    pub(crate) fn complete(self, output: T) {
+        lock(&self.shared).output = Some(output);
+    }
+}
+
+impl<T> Drop for Completer<T> {
+    /// Wakes the future, with the output if there is one.
+    fn drop(&mut self) {
+        let mut shared = lock(&self.shared);
+        shared.completer_dropped = true;
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+fn lock<T>(shared: &Shared<T>) -> MutexGuard<'_, Handoff<T>> {
+    // The handoff is only written field by field, a panic can not leave it half changed.
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The future of [`spawn_blocking`] and [`oneshot`].
+pub(crate) struct Blocking<T> {
+    shared: Shared<T>,
+}
+
+impl<T> Future for Blocking<T> {
+    type Output = Result<T, Canceled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<T, Canceled>> {
+        let mut shared = lock(&self.shared);
+        match shared.output.take() {
+            Some(output) => Poll::Ready(Ok(output)),
+            None if shared.completer_dropped => Poll::Ready(Err(Canceled)),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
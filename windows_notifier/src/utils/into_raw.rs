@@ -1,6 +1,63 @@
+use std::io::Write;
+
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+
 use crate::error::Result;
-use windows::Data::Xml::Dom::{XmlDocument, XmlElement};
+use crate::raw::Extra;
+#[cfg(test)]
+use crate::raw::{FromXML, RawElement};
 
 pub trait ToXML {
-    fn into_raw(self, doc: &XmlDocument) -> Result<XmlElement>;
+    fn write_xml<W: Write>(&self, writer: &mut Writer<W>) -> Result<()>;
+}
+
+/// Writes `start` as an element that contains `text`, followed by any unknown elements.
+pub(crate) fn write_text<W: Write>(
+    writer: &mut Writer<W>,
+    start: BytesStart,
+    text: &str,
+    extra: &Extra,
+) -> Result<()> {
+    write_children(writer, start, |writer| {
+        writer.write_event(Event::Text(BytesText::new(text)))?;
+        extra.write_elements(writer)
+    })
+}
+
+/// Writes `start` as an element whose content is written by `children`.
+pub(crate) fn write_children<W: Write>(
+    writer: &mut Writer<W>,
+    start: BytesStart,
+    children: impl FnOnce(&mut Writer<W>) -> Result<()>,
+) -> Result<()> {
+    let end = BytesEnd::new(String::from_utf8_lossy(start.name().as_ref()).into_owned());
+    writer.write_event(Event::Start(start))?;
+    children(writer)?;
+    writer.write_event(Event::End(end))?;
+    Ok(())
+}
+
+/// Writes `start` as an element with no content other than any unknown elements.
+pub(crate) fn write_empty<W: Write>(
+    writer: &mut Writer<W>,
+    start: BytesStart,
+    extra: &Extra,
+) -> Result<()> {
+    if extra.elements().is_empty() {
+        writer.write_event(Event::Empty(start))?;
+        Ok(())
+    } else {
+        write_children(writer, start, |writer| extra.write_elements(writer))
+    }
+}
+
+/// Writes `element` on its own and reads it back, for the tests of the tags.
+#[cfg(test)]
+pub(crate) fn round_trip<T: ToXML + FromXML>(element: &T) -> (String, T) {
+    let mut writer = Writer::new(Vec::new());
+    element.write_xml(&mut writer).unwrap();
+    let xml = String::from_utf8(writer.into_inner()).unwrap();
+    let parsed = T::from_raw(&RawElement::from_reader(xml.as_bytes()).unwrap()).unwrap();
+    (xml, parsed)
 }
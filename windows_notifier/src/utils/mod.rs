@@ -1 +1,4 @@
-pub mod into_raw;
\ No newline at end of file
+pub mod blocking;
+pub mod into_raw;
+pub mod time;
+pub mod timer;
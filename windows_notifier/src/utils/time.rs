@@ -0,0 +1,160 @@
+//! ISO 8601 timestamps, like `2017-04-15T19:45:00Z`, as windows expects them in the toast xml.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Writes `time` in UTC, with milliseconds only if it has any.
+pub(crate) fn format_iso8601(time: SystemTime) -> String {
+    let (seconds, millis) = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_secs() as i64, since.subsec_millis()),
+        Err(err) => {
+            let before = err.duration();
+            let millis = (1000 - before.subsec_millis()) % 1000;
+            (-(before.as_secs() as i64) - (millis > 0) as i64, millis)
+        }
+    };
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let time_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+    let mut formatted = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60
+    );
+    if millis > 0 {
+        formatted.push_str(&format!(".{:03}", millis));
+    }
+    formatted.push('Z');
+    formatted
+}
+
+/// Reads `YYYY-MM-DDTHH:MM:SS`, with optional fractional seconds, followed by `Z` or an offset like `+02:00`.
+pub(crate) fn parse_iso8601(value: &str) -> Option<SystemTime> {
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = value.get(range)?;
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())?
+    };
+    let separators = value.as_bytes();
+    if separators.get(4) != Some(&b'-')
+        || separators.get(7) != Some(&b'-')
+        || !matches!(separators.get(10), Some(b'T' | b't'))
+        || separators.get(13) != Some(&b':')
+        || separators.get(16) != Some(&b':')
+    {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    let mut rest = &value[19..];
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        let padded = format!("{:0<9}", &fraction[..digits.min(9)]);
+        nanos = padded.parse().ok()?;
+        rest = &fraction[digits..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            if rest.len() != 6 || rest.as_bytes()[3] != b':' {
+                return None;
+            }
+            let hours: i64 = rest[1..3].parse().ok()?;
+            let minutes: i64 = rest[4..6].parse().ok()?;
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+
+    let seconds =
+        days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second
+            - offset;
+    let time = match u64::try_from(seconds) {
+        Ok(seconds) => UNIX_EPOCH + Duration::from_secs(seconds),
+        Err(_) => UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs()),
+    };
+    Some(time + Duration::from_nanos(nanos))
+}
+
+/// Days since 1970-01-01 of a date in the proleptic gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date of a day since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{format_iso8601, parse_iso8601};
+
+    #[test]
+    fn round_trips_timestamps() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_492_285_500);
+        assert_eq!(format_iso8601(time), "2017-04-15T19:45:00Z");
+        assert_eq!(parse_iso8601("2017-04-15T19:45:00Z"), Some(time));
+        assert_eq!(parse_iso8601("2017-04-15T21:45:00+02:00"), Some(time));
+
+        let time = time + Duration::from_millis(250);
+        assert_eq!(format_iso8601(time), "2017-04-15T19:45:00.250Z");
+        assert_eq!(parse_iso8601(&format_iso8601(time)), Some(time));
+
+        let before = UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(format_iso8601(before), "1969-12-31T23:59:58.500Z");
+        assert_eq!(parse_iso8601(&format_iso8601(before)), Some(before));
+        assert_eq!(
+            format_iso8601(parse_iso8601("2024-02-29T00:00:00Z").unwrap()),
+            "2024-02-29T00:00:00Z"
+        );
+
+        assert_eq!(parse_iso8601("2017-04-15 19:45:00Z"), None);
+        assert_eq!(parse_iso8601("2017-13-15T19:45:00Z"), None);
+        assert_eq!(parse_iso8601("2017-04-15T19:45:00"), None);
+    }
+}
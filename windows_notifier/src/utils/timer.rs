@@ -0,0 +1,59 @@
+//! Running callbacks once a timeout passed, all on one thread shared by the whole process.
+
+use std::sync::{Condvar, Mutex, MutexGuard, Once, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+/// The callbacks by their deadline, the next one last.
+static PENDING: Mutex<Vec<(Instant, Callback)>> = Mutex::new(Vec::new());
+static CHANGED: Condvar = Condvar::new();
+static STARTED: Once = Once::new();
+
+/// Runs `callback` on the timer thread once `timeout` passed.
+/// A timeout too long for an [`Instant`] never passes.
+pub(crate) fn after(timeout: Duration, callback: impl FnOnce() + Send + 'static) {
+    let Some(deadline) = Instant::now().checked_add(timeout) else {
+        return;
+    };
+    STARTED.call_once(|| {
+        thread::spawn(run);
+    });
+    let mut pending = lock();
+    let index = pending.partition_point(|(due, _)| *due > deadline);
+    pending.insert(index, (deadline, Box::new(callback)));
+    CHANGED.notify_one();
+}
+
+fn lock() -> MutexGuard<'static, Vec<(Instant, Callback)>> {
+    // The callbacks run without the lock, a panic can not leave the list half changed.
+    PENDING.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The timer thread, running every callback once its deadline passed.
+fn run() {
+    let mut pending = lock();
+    loop {
+        let now = Instant::now();
+        let next = pending.last().map(|(deadline, _)| *deadline);
+        pending = match next {
+            Some(deadline) if deadline <= now => {
+                if let Some((_, callback)) = pending.pop() {
+                    drop(pending);
+                    callback();
+                }
+                lock()
+            }
+            Some(deadline) => {
+                CHANGED
+                    .wait_timeout(pending, deadline - now)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0
+            }
+            None => CHANGED
+                .wait(pending)
+                .unwrap_or_else(PoisonError::into_inner),
+        };
+    }
+}
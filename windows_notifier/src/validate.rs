@@ -0,0 +1,424 @@
+//! Checks for the documented toast rules that windows enforces by silently dropping the toast.
+
+use std::fmt;
+
+use crate::tags::activation::{is_uri, ActivationType, AfterActivationBehavior, SystemAction};
+use crate::tags::binding::BindingChild;
+use crate::tags::commands::Senario;
+use crate::tags::input::Input;
+use crate::tags::toast::{Duration, Scenarios};
+use crate::Toast;
+
+/// The most actions a toast can have.
+pub const MAX_ACTIONS: usize = 5;
+/// The most inputs a toast can have.
+pub const MAX_INPUTS: usize = 5;
+/// The most selections a selection input can have.
+pub const MAX_SELECTIONS: usize = 5;
+/// The most text elements the binding can have, not counting the attribution text.
+pub const MAX_TEXTS: usize = 3;
+
+/// How bad a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// The toast will show, but not all of it.
+    Warning,
+    /// Windows will not show the toast.
+    Error,
+}
+
+/// A broken rule found by [`Toast::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The element the rule applies to, like `toast/actions/action[6]`.
+    pub path: String,
+    pub message: String,
+    /// The limit that was exceeded, for diagnostics about too many elements.
+    pub limit: Option<usize>,
+}
+
+impl Diagnostic {
+    fn error(path: impl Into<String>, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            path: path.into(),
+            message: message.into(),
+            limit: None,
+        }
+    }
+
+    /// An error for the element at `path` being one more than `limit` allows.
+    fn over_limit(path: impl Into<String>, limit: usize, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            limit: Some(limit),
+            ..Diagnostic::error(path, message)
+        }
+    }
+
+    fn warning(path: impl Into<String>, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            path: path.into(),
+            message: message.into(),
+            limit: None,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", severity, self.path, self.message)
+    }
+}
+
+impl<S, C> Toast<S, C> {
+    /// Check the toast against the documented schema rules and limits.
+    ///
+    /// Returns every broken rule, an empty list means the toast is fine to show.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        self.validate_texts(&mut diagnostics);
+        self.validate_audio(&mut diagnostics);
+        self.validate_inputs(&mut diagnostics);
+        self.validate_actions(&mut diagnostics);
+        self.validate_activation(&mut diagnostics);
+        self.validate_scenario(&mut diagnostics);
+        diagnostics
+    }
+
+    fn validate_texts(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let mut texts = 0;
+        let mut attributions = 0;
+        for child in &self.visual.binding.children {
+            let BindingChild::Text(text) = child else {
+                continue;
+            };
+            if text.bottem_text {
+                attributions += 1;
+                if attributions > 1 {
+                    diagnostics.push(Diagnostic::warning(
+                        format!("toast/visual/binding/text[{}]", texts + attributions),
+                        "only one attribution text is shown",
+                    ));
+                }
+            } else {
+                texts += 1;
+                if texts > MAX_TEXTS {
+                    diagnostics.push(Diagnostic::over_limit(
+                        format!("toast/visual/binding/text[{}]", texts + attributions),
+                        MAX_TEXTS,
+                        format!("the binding can have at most {} text elements", MAX_TEXTS),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn validate_audio(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let raw_audio = self
+            .extra
+            .elements()
+            .iter()
+            .filter(|element| element.name == "audio")
+            .count();
+        let audio = raw_audio + usize::from(self.audio.is_some());
+        if audio > 1 {
+            diagnostics.push(Diagnostic::error(
+                "toast/audio[2]",
+                "a toast can have only one audio element",
+            ));
+        }
+    }
+
+    fn validate_inputs(&self, diagnostics: &mut Vec<Diagnostic>) {
+        for (index, input) in self.actions.inputs.iter().enumerate() {
+            let path = format!("toast/actions/input[{}]", index + 1);
+            if index == MAX_INPUTS {
+                diagnostics.push(Diagnostic::over_limit(
+                    &path,
+                    MAX_INPUTS,
+                    format!("a toast can have at most {} inputs", MAX_INPUTS),
+                ));
+            }
+
+            let Input::Selection {
+                default_selection,
+                selections,
+                ..
+            } = input
+            else {
+                continue;
+            };
+            if selections.len() > MAX_SELECTIONS {
+                diagnostics.push(Diagnostic::over_limit(
+                    format!("{}/selection[{}]", path, MAX_SELECTIONS + 1),
+                    MAX_SELECTIONS,
+                    format!("an input can have at most {} selections", MAX_SELECTIONS),
+                ));
+            }
+            if let Some(default_selection) = default_selection {
+                if !selections.iter().any(|s| &s.id == default_selection) {
+                    diagnostics.push(Diagnostic::error(
+                        &path,
+                        format!(
+                            "defaultInput \"{}\" does not match any selection",
+                            default_selection
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn validate_actions(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let actions = &self.actions.actions;
+        if actions.len() > MAX_ACTIONS {
+            diagnostics.push(Diagnostic::over_limit(
+                format!("toast/actions/action[{}]", MAX_ACTIONS + 1),
+                MAX_ACTIONS,
+                format!("a toast can have at most {} actions", MAX_ACTIONS),
+            ));
+        }
+
+        for (index, action) in actions.iter().enumerate() {
+            if let Some(hint_input_id) = &action.hint_input_id {
+                if !self
+                    .actions
+                    .inputs
+                    .iter()
+                    .any(|i| i.get_id() == hint_input_id)
+                {
+                    diagnostics.push(Diagnostic::error(
+                        format!("toast/actions/action[{}]", index + 1),
+                        format!(
+                            "hint-inputId \"{}\" does not match any input",
+                            hint_input_id
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Context menu entries are not buttons, so they do not take part in the imageUri rule.
+        let buttons = actions.iter().enumerate().filter(|(_, a)| !a.context_menu);
+        let with_image = buttons
+            .clone()
+            .filter(|(_, a)| a.image_uri.is_some())
+            .count();
+        if with_image > 0 && with_image < buttons.clone().count() {
+            for (index, _) in buttons.filter(|(_, a)| a.image_uri.is_none()) {
+                diagnostics.push(Diagnostic::error(
+                    format!("toast/actions/action[{}]", index + 1),
+                    "if any button has an imageUri, all buttons must have one",
+                ));
+            }
+        }
+    }
+
+    /// The activation rules, which the protocol and system constructors follow but parsed toasts do not have to.
+    fn validate_activation(&self, diagnostics: &mut Vec<Diagnostic>) {
+        check_protocol(
+            diagnostics,
+            "toast",
+            self.activation_type,
+            self.launch.as_deref(),
+            self.target_application_pfn.as_deref(),
+        );
+        for (index, action) in self.actions.actions.iter().enumerate() {
+            let path = format!("toast/actions/action[{}]", index + 1);
+            check_protocol(
+                diagnostics,
+                &path,
+                action.activation_type,
+                Some(&action.arguments),
+                action.target_application_pfn.as_deref(),
+            );
+            if action.activation_type == Some(ActivationType::System)
+                && SystemAction::from_arguments(&action.arguments).is_none()
+            {
+                diagnostics.push(Diagnostic::error(
+                    &path,
+                    format!(
+                        "system activation needs the arguments snooze or dismiss, not \"{}\"",
+                        action.arguments
+                    ),
+                ));
+            }
+            if action.after_activation_behavior == Some(AfterActivationBehavior::PendingUpdate)
+                && action.activation_type != Some(ActivationType::Background)
+            {
+                diagnostics.push(Diagnostic::warning(
+                    &path,
+                    "afterActivationBehavior pendingUpdate only applies to background actions",
+                ));
+            }
+        }
+        if let Some(header) = &self.header {
+            check_protocol(
+                diagnostics,
+                "toast/header",
+                header.activation_type,
+                Some(&header.arguments),
+                header.target_application_pfn.as_deref(),
+            );
+        }
+
+        if self.activation_type == Some(ActivationType::System) {
+            diagnostics.push(Diagnostic::error(
+                "toast",
+                "only actions can use system activation",
+            ));
+        }
+        if let Some(activation_type @ (ActivationType::Background | ActivationType::System)) = self
+            .header
+            .as_ref()
+            .and_then(|header| header.activation_type)
+        {
+            diagnostics.push(Diagnostic::error(
+                "toast/header",
+                format!("a header can not use {} activation", activation_type),
+            ));
+        }
+        if self.actions.snooze_and_dismiss
+            && (!self.actions.inputs.is_empty()
+                || self.actions.actions.iter().any(|a| !a.context_menu))
+        {
+            diagnostics.push(Diagnostic::error(
+                "toast/actions",
+                "hint-systemCommands can only be combined with context menu actions",
+            ));
+        }
+    }
+
+    /// The scenario rules, which the typestate enforces for toasts that are built but not for parsed ones.
+    fn validate_scenario(&self, diagnostics: &mut Vec<Diagnostic>) {
+        if self.scenario == Some(Scenarios::Reminder) && !self.actions.keeps_reminder() {
+            diagnostics.push(Diagnostic::warning(
+                "toast",
+                "a reminder without a background or system action is shown as a normal toast",
+            ));
+        }
+
+        let looping = self.audio.as_ref().is_some_and(|audio| audio.loop_);
+        let loops_by_scenario = matches!(
+            self.scenario,
+            Some(Scenarios::Alarm) | Some(Scenarios::IncomingCall)
+        );
+        if looping && !loops_by_scenario && self.duration != Some(Duration::Long) {
+            diagnostics.push(Diagnostic::warning(
+                "toast/audio",
+                "looping audio needs a long duration or an alarm or incoming call scenario",
+            ));
+        }
+
+        if let Some(command) = self.commands.first() {
+            let scenario = match command.scenario {
+                Senario::Alarm(_) => Scenarios::Alarm,
+                Senario::IncomingCall(_) => Scenarios::IncomingCall,
+            };
+            if self.scenario != Some(scenario) {
+                diagnostics.push(Diagnostic::warning(
+                    "toast/commands",
+                    format!("commands for the {} scenario are ignored", scenario),
+                ));
+            }
+        }
+    }
+}
+
+/// The protocol rules shared by the toast, its actions and its header.
+fn check_protocol(
+    diagnostics: &mut Vec<Diagnostic>,
+    path: &str,
+    activation_type: Option<ActivationType>,
+    arguments: Option<&str>,
+    pfn: Option<&str>,
+) {
+    if activation_type == Some(ActivationType::Protocol) && !arguments.is_some_and(is_uri) {
+        diagnostics.push(Diagnostic::error(
+            path,
+            "protocol activation needs an absolute URI as its arguments",
+        ));
+    }
+    if pfn.is_some() && activation_type != Some(ActivationType::Protocol) {
+        diagnostics.push(Diagnostic::warning(
+            path,
+            "protocolActivationTargetApplicationPfn is only used by protocol activation",
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::action::Action;
+    use crate::tags::audio::{Audio, Notification};
+    use crate::tags::input::{Input, Selection};
+    use crate::validate::Severity;
+    use crate::*;
+
+    #[test]
+    fn reports_broken_rules() {
+        let mut toast = Toast::new().unwrap();
+        toast
+            .add_input(Input::new_selection(
+                "time",
+                vec![Selection::new("5", "5 minutes")],
+                Some("10"),
+            ))
+            .unwrap();
+        for i in 0..6 {
+            let action = Action::new(i.to_string(), i.to_string());
+            let action = match i {
+                0 => action.image_uri("icon.png".into()),
+                1 => action.hint_input_id("missing".into()),
+                _ => action,
+            };
+            // add_action refuses a sixth action, so it is pushed directly like a parsed toast would have it.
+            toast.actions.actions.push(action);
+        }
+        let err = toast.add_action(Action::new("7".into(), "7")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "toast/actions/action[7] is over the limit of 5"
+        );
+
+        let diagnostics = toast.validate();
+        let paths: Vec<_> = diagnostics.iter().map(|d| d.path.as_str()).collect();
+        assert!(paths.contains(&"toast/actions/input[1]"));
+        assert!(paths.contains(&"toast/actions/action[6]"));
+        assert!(paths.contains(&"toast/actions/action[2]"));
+        assert_eq!(diagnostics.len(), 8);
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+
+        assert!(Toast::new().unwrap().validate().is_empty());
+    }
+
+    #[test]
+    fn reports_scenario_rules() {
+        let xml = r#"<toast scenario="reminder">
+            <visual><binding template="ToastGeneric"><text>Wake up</text></binding></visual>
+            <audio src="ms-winsoundevent:Notification.Looping.Alarm" loop="true"/>
+            <commands scenario="alarm"><command id="snooze" arguments=""/></commands>
+        </toast>"#;
+        let diagnostics = Toast::from_xml(xml).unwrap().validate();
+        let paths: Vec<_> = diagnostics.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, ["toast", "toast/audio", "toast/commands"]);
+
+        let toast = Toast::new()
+            .unwrap()
+            .scenario(tags::toast::Reminder::new(Action::new("Ok".into(), "ok")))
+            .unwrap()
+            .add_audio(
+                Audio::new(Notification::LoopingAlarm)
+                    .loop_()
+                    .long_duration(),
+            )
+            .unwrap();
+        assert!(toast.validate().is_empty());
+    }
+}
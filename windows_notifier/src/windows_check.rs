@@ -16,8 +16,7 @@ mod internal {
 #[cfg(target_env = "gnu")]
 mod internal {
     use windows::{
-        core::PCSTR,
-        s,
+        core::{s, PCSTR},
         Win32::System::LibraryLoader::{GetModuleHandleA, GetProcAddress},
     };
 
@@ -54,4 +53,4 @@ pub fn is_newer_than_windows81() -> bool {
         internal::RtlGetNtVersionNumbers(&mut major, &mut minor, &mut build);
     }
     major > 6
-}
\ No newline at end of file
+}